@@ -0,0 +1,4 @@
+ALTER TABLE IF EXISTS public.job_requests
+    DROP CONSTRAINT IF EXISTS job_requests_result_encoding_check,
+    DROP COLUMN IF EXISTS result_encoding,
+    DROP COLUMN IF EXISTS result_compressed;
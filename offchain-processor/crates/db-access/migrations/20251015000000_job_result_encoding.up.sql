@@ -0,0 +1,7 @@
+-- Results are stored either as JSONB in `result`, or gzipped JSON in `result_compressed`
+ALTER TABLE public.job_requests
+    ADD COLUMN IF NOT EXISTS result_compressed BYTEA,
+    ADD COLUMN IF NOT EXISTS result_encoding VARCHAR(10) NOT NULL DEFAULT 'json',
+    ADD CONSTRAINT job_requests_result_encoding_check CHECK (
+        result_encoding::TEXT = ANY (ARRAY['json'::TEXT, 'gzip'::TEXT])
+    );
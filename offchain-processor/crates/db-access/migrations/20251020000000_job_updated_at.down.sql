@@ -0,0 +1,2 @@
+ALTER TABLE IF EXISTS public.job_requests
+    DROP COLUMN IF EXISTS updated_at;
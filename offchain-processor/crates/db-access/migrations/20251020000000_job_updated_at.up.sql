@@ -0,0 +1,5 @@
+-- Time a job request last changed status, existing jobs are taken as unchanged since created
+ALTER TABLE public.job_requests
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+UPDATE public.job_requests SET updated_at = created_at;
@@ -0,0 +1,11 @@
+-- Job created for each `Idempotency-Key` a pricing data request was sent with, so retried
+-- requests are answered with the original job
+CREATE TABLE IF NOT EXISTS public.idempotency_keys (
+    idempotency_key VARCHAR(255) NOT NULL,
+    job_id VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT idempotency_keys_pkey PRIMARY KEY (idempotency_key)
+);
+
+ALTER TABLE IF EXISTS public.idempotency_keys
+    OWNER TO postgres;
@@ -0,0 +1,81 @@
+use crate::models::JobStatus;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+// Status changes buffered per job for subscribers that fall behind
+const JOB_EVENTS_CAPACITY: usize = 16;
+
+/// Broadcasts the status changes of each job to the subscribers of that job.
+#[derive(Debug, Default)]
+pub struct JobStatusEvents {
+    senders: Mutex<HashMap<String, broadcast::Sender<JobStatus>>>,
+}
+
+impl JobStatusEvents {
+    /// Subscribes to the status changes of `job_id` from now on. Once the job reaches a terminal
+    /// status, the receiver gets it and is then closed.
+    pub fn subscribe(&self, job_id: &str) -> broadcast::Receiver<JobStatus> {
+        let mut senders = self.senders.lock().unwrap();
+        // Jobs whose subscribers all left without a terminal status would otherwise stay forever
+        senders.retain(|_, sender| sender.receiver_count() > 0);
+        senders
+            .entry(job_id.to_string())
+            .or_insert_with(|| broadcast::channel(JOB_EVENTS_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Sends `status` to the subscribers of `job_id`, if it has any.
+    pub fn publish(&self, job_id: &str, status: JobStatus) {
+        let mut senders = self.senders.lock().unwrap();
+        let Some(sender) = senders.get(job_id) else {
+            return;
+        };
+
+        // Only fails when every subscriber is gone, which is handled below
+        let _ = sender.send(status);
+        // Dropping the sender closes the subscriptions once they have read the terminal status
+        if status.is_terminal() || sender.receiver_count() == 0 {
+            senders.remove(job_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    #[test]
+    fn test_subscriber_receives_status_changes_until_terminal() {
+        let events = JobStatusEvents::default();
+        let mut receiver = events.subscribe("job_1");
+
+        events.publish("job_1", JobStatus::Pending);
+        events.publish("job_1", JobStatus::Completed);
+
+        assert_eq!(receiver.try_recv(), Ok(JobStatus::Pending));
+        assert_eq!(receiver.try_recv(), Ok(JobStatus::Completed));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Closed));
+    }
+
+    #[test]
+    fn test_subscriber_only_receives_its_job() {
+        let events = JobStatusEvents::default();
+        let mut receiver = events.subscribe("job_1");
+
+        events.publish("job_2", JobStatus::Failed);
+
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_publish_forgets_jobs_without_subscribers() {
+        let events = JobStatusEvents::default();
+        drop(events.subscribe("job_1"));
+
+        events.publish("job_1", JobStatus::Pending);
+
+        assert!(events.senders.lock().unwrap().is_empty());
+    }
+}
@@ -7,7 +7,7 @@ pub struct ApiKey {
     pub name: Option<String>,
 }
 
-#[derive(sqlx::Type, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[sqlx(type_name = "TEXT")]
 pub enum JobStatus {
     Pending,
@@ -15,6 +15,13 @@ pub enum JobStatus {
     Failed,
 }
 
+impl JobStatus {
+    /// Whether the job is done, no longer changing status.
+    pub const fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
 impl fmt::Display for JobStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -25,10 +32,60 @@ impl fmt::Display for JobStatus {
     }
 }
 
+/// How the result of a job request is stored.
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum ResultEncoding {
+    /// Plain JSONB in the `result` column.
+    Json,
+    /// Gzipped JSON in the `result_compressed` column.
+    Gzip,
+}
+
+impl fmt::Display for ResultEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Gzip => write!(f, "gzip"),
+        }
+    }
+}
+
 #[derive(sqlx::FromRow, Debug)]
 pub struct JobRequest {
     pub job_id: String,
     pub status: JobStatus,
     pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
     pub result: Option<serde_json::Value>,
 }
+
+/// A job as listed, without its result.
+#[derive(sqlx::FromRow, Debug)]
+pub struct JobRequestListing {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Values proven for a job. A value is only set once its proof has been generated.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvenValues {
+    #[serde(default)]
+    pub twap: Option<f64>,
+    #[serde(default)]
+    pub reserve_price: Option<f64>,
+    #[serde(default)]
+    pub max_return: Option<f64>,
+}
+
+/// Typed shape of the `result` column of a job request.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobResult {
+    #[serde(default)]
+    pub outputs: ProvenValues,
+    #[serde(default)]
+    pub receipt_ref: Option<String>,
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+}
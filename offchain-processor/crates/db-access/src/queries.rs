@@ -1,8 +1,60 @@
+use std::collections::HashMap;
+use std::io::Read;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::models::{JobRequest, JobStatus};
+use crate::models::{JobRequest, JobRequestListing, JobResult, JobStatus, ResultEncoding};
 use crate::OffchainProcessorDbConnection;
 use eyre::Result;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+// A job result as stored: in `result`, or gzipped in `result_compressed`
+struct StoredResult {
+    result: Option<serde_json::Value>,
+    result_compressed: Option<Vec<u8>>,
+    result_encoding: ResultEncoding,
+}
+
+fn encode_result(
+    db: &OffchainProcessorDbConnection,
+    result: Option<serde_json::Value>,
+) -> Result<StoredResult, sqlx::Error> {
+    let Some(result) = result.as_ref().filter(|_| db.compress_results()) else {
+        return Ok(StoredResult {
+            result,
+            result_compressed: None,
+            result_encoding: ResultEncoding::Json,
+        });
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, result).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+    Ok(StoredResult {
+        result: None,
+        result_compressed: Some(compressed),
+        result_encoding: ResultEncoding::Gzip,
+    })
+}
+
+fn decode_result(stored: StoredResult) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    match stored.result_encoding {
+        ResultEncoding::Json => Ok(stored.result),
+        ResultEncoding::Gzip => stored
+            .result_compressed
+            .map(|compressed| {
+                let mut json = Vec::new();
+                GzDecoder::new(compressed.as_slice())
+                    .read_to_end(&mut json)
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                serde_json::from_slice(&json).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+            })
+            .transpose(),
+    }
+}
 
 pub async fn create_job_request(
     db: Arc<OffchainProcessorDbConnection>,
@@ -24,20 +76,65 @@ pub async fn get_job_request(
     db: Arc<OffchainProcessorDbConnection>,
     job_id: &str,
 ) -> Result<Option<JobRequest>, sqlx::Error> {
-    sqlx::query_as!(
-        JobRequest,
+    let row = sqlx::query!(
         r#"
         SELECT 
             job_id,
             status as "status: JobStatus",
             created_at,
-            result
+            updated_at,
+            result,
+            result_compressed,
+            result_encoding as "result_encoding: ResultEncoding"
         FROM job_requests
         WHERE job_id = $1
         "#,
         job_id
     )
     .fetch_optional(&db.db_connection().pool)
+    .await?;
+
+    row.map(|row| {
+        Ok(JobRequest {
+            job_id: row.job_id,
+            status: row.status,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            result: decode_result(StoredResult {
+                result: row.result,
+                result_compressed: row.result_compressed,
+                result_encoding: row.result_encoding,
+            })?,
+        })
+    })
+    .transpose()
+}
+
+/// Lists job requests, newest first, optionally only those with the given status. Their
+/// results aren't read, keeping the listing cheap however large they are.
+pub async fn list_job_requests(
+    db: Arc<OffchainProcessorDbConnection>,
+    limit: i64,
+    offset: i64,
+    status: Option<JobStatus>,
+) -> Result<Vec<JobRequestListing>, sqlx::Error> {
+    sqlx::query_as!(
+        JobRequestListing,
+        r#"
+        SELECT
+            job_id,
+            status as "status: JobStatus",
+            created_at
+        FROM job_requests
+        WHERE $3::TEXT IS NULL OR status = $3
+        ORDER BY created_at DESC, job_id DESC
+        LIMIT $1 OFFSET $2
+        "#,
+        limit,
+        offset,
+        status.map(|status| status.to_string())
+    )
+    .fetch_all(&db.db_connection().pool)
     .await
 }
 
@@ -47,40 +144,151 @@ pub async fn update_job_status(
     status: JobStatus,
     result: Option<serde_json::Value>,
 ) -> Result<(), sqlx::Error> {
+    let stored = encode_result(&db, result)?;
+
     sqlx::query!(
         r#"
         UPDATE job_requests
-        SET status = $2, result = $3
+        SET status = $2, result = $3, result_compressed = $4, result_encoding = $5,
+            updated_at = CURRENT_TIMESTAMP
         WHERE job_id = $1
         "#,
         job_id,
         status.to_string(),
-        result
+        stored.result,
+        stored.result_compressed,
+        stored.result_encoding.to_string()
     )
     .execute(&db.db_connection().pool)
     .await?;
 
+    db.job_events().publish(job_id, status);
+
     Ok(())
 }
 
 pub async fn update_job_result(
     db: Arc<OffchainProcessorDbConnection>,
     job_id: &str,
-    status: &str,
-    result: serde_json::Value,
-) -> Result<()> {
-    sqlx::query!(
+    status: JobStatus,
+    result: &JobResult,
+) -> Result<(), sqlx::Error> {
+    let result = serde_json::to_value(result).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+    update_job_status(db, job_id, status, Some(result)).await
+}
+
+/// Returns the typed result of a job, or `None` if the job doesn't exist or has no result yet.
+pub async fn get_job_result(
+    db: Arc<OffchainProcessorDbConnection>,
+    job_id: &str,
+) -> Result<Option<JobResult>, sqlx::Error> {
+    let row = sqlx::query!(
         r#"
-        UPDATE job_requests
-        SET status = $2, result = $3
+        SELECT
+            result,
+            result_compressed,
+            result_encoding as "result_encoding: ResultEncoding"
+        FROM job_requests
         WHERE job_id = $1
         "#,
-        job_id,
-        status,
-        result
+        job_id
+    )
+    .fetch_optional(&db.db_connection().pool)
+    .await?;
+
+    let result = match row {
+        Some(row) => decode_result(StoredResult {
+            result: row.result,
+            result_compressed: row.result_compressed,
+            result_encoding: row.result_encoding,
+        })?,
+        None => None,
+    };
+
+    result
+        .map(|result| serde_json::from_value(result).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+        .transpose()
+}
+
+/// Reserves an idempotency key for `job_id` before the job is created, so that concurrent
+/// requests with the same key can't both create one. An entry stored more than `ttl` ago is
+/// replaced. Returns `None` once the key is reserved, or the job it is already reserved for.
+pub async fn reserve_idempotency_key(
+    db: Arc<OffchainProcessorDbConnection>,
+    idempotency_key: &str,
+    job_id: &str,
+    ttl: Duration,
+) -> Result<Option<String>, sqlx::Error> {
+    let pool = &db.db_connection().pool;
+    loop {
+        let reserved = sqlx::query_scalar!(
+            r#"
+            INSERT INTO idempotency_keys (idempotency_key, job_id)
+            VALUES ($1, $2)
+            ON CONFLICT (idempotency_key) DO UPDATE
+            SET job_id = EXCLUDED.job_id, created_at = CURRENT_TIMESTAMP
+            WHERE idempotency_keys.created_at <= CURRENT_TIMESTAMP - make_interval(secs => $3)
+            RETURNING job_id
+            "#,
+            idempotency_key,
+            job_id,
+            ttl.as_secs_f64()
+        )
+        .fetch_optional(pool)
+        .await?;
+        if reserved.is_some() {
+            return Ok(None);
+        }
+
+        let existing = sqlx::query_scalar!(
+            "SELECT job_id FROM idempotency_keys WHERE idempotency_key = $1",
+            idempotency_key
+        )
+        .fetch_optional(pool)
+        .await?;
+        // Otherwise the entry was released in between, so try reserving it again
+        if existing.is_some() {
+            return Ok(existing);
+        }
+    }
+}
+
+/// Releases an idempotency key reserved for `job_id` whose job couldn't be created, so that a
+/// retry can create it.
+pub async fn release_idempotency_key(
+    db: Arc<OffchainProcessorDbConnection>,
+    idempotency_key: &str,
+    job_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM idempotency_keys WHERE idempotency_key = $1 AND job_id = $2",
+        idempotency_key,
+        job_id
     )
     .execute(&db.db_connection().pool)
     .await?;
 
     Ok(())
 }
+
+pub async fn count_jobs_by_status(
+    db: Arc<OffchainProcessorDbConnection>,
+) -> Result<HashMap<JobStatus, i64>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            status as "status: JobStatus",
+            COUNT(*) as "count!"
+        FROM job_requests
+        GROUP BY status
+        "#
+    )
+    .fetch_all(&db.db_connection().pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.status, row.count))
+        .collect())
+}
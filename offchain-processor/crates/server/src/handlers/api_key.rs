@@ -2,6 +2,7 @@ use axum::{extract::State, http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::types::ErrorResponse;
 use crate::AppState;
 use db_access::auth::add_api_key;
 
@@ -18,12 +19,15 @@ pub struct ApiKeyResponse {
 pub async fn create_api_key(
     State(state): State<AppState>,
     Json(payload): Json<ApiKeyRequest>,
-) -> Result<Json<ApiKeyResponse>, StatusCode> {
+) -> Result<Json<ApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
     let api_key = Uuid::new_v4().to_string();
 
     if let Err(e) = add_api_key(state.offchain_processor_db, api_key.clone(), payload.name).await {
-        tracing::error!("Failed to store API key: {:?}", e);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        tracing::error!("Failed to store API key");
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::internal(&e)),
+        ));
     }
 
     Ok(Json(ApiKeyResponse { api_key }))
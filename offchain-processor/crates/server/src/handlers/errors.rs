@@ -0,0 +1,22 @@
+use uuid::Uuid;
+
+use crate::types::ErrorResponse;
+
+// Message returned to clients in place of the details of an internal error
+pub const INTERNAL_ERROR_MESSAGE: &str = "An internal error occurred. Please try again later.";
+
+// Logs the full error server-side and returns an id the client can quote for support
+pub fn report_internal_error(error: &impl std::fmt::Debug) -> String {
+    let error_id = Uuid::new_v4().to_string();
+    tracing::error!(error_id = %error_id, "Internal server error: {:?}", error);
+    error_id
+}
+
+impl ErrorResponse {
+    pub fn internal(error: &impl std::fmt::Debug) -> Self {
+        Self {
+            error: INTERNAL_ERROR_MESSAGE.to_string(),
+            error_id: Some(report_internal_error(error)),
+        }
+    }
+}
@@ -1,18 +1,36 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
-    types::{GetJobStatusResponseEnum, JobResponse, PitchLakeJobRequest},
-    AppState,
+    types::{
+        GetJobResultResponseEnum, GetJobStatusResponseEnum, JobListResponseEnum, JobResponse,
+        JobsSummaryResponseEnum, PitchLakeJobRequest,
+    },
+    AppState, DEFAULT_IDEMPOTENCY_KEY_TTL, DEFAULT_MAX_CLOCK_SKEW,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+    Json,
 };
-use axum::{extract::State, http::StatusCode, Json};
 use db_access::{
-    models::JobStatus, queries::create_job_request, DbConnection, OffchainProcessorDbConnection,
+    models::{JobResult, JobStatus},
+    queries::{create_job_request, get_job_result, update_job_result},
+    DbConnection, OffchainProcessorDbConnection,
 };
 use lazy_static::lazy_static;
 use sqlx::postgres::PgPoolOptions;
 use testcontainers::{clients::Cli, images::postgres::Postgres as PostgresImage, Container};
 
-use super::{get_pricing_data::get_pricing_data, job_status::get_job_status};
+use super::{
+    get_pricing_data::{get_pricing_data, IDEMPOTENCY_KEY_HEADER},
+    job_events::get_job_events,
+    job_result::get_result,
+    job_status::get_job_status,
+    jobs_summary::get_jobs_summary,
+    list_jobs::{list_jobs, ListJobsQuery},
+};
 
 lazy_static! {
     static ref DOCKER: Cli = Cli::default();
@@ -44,7 +62,10 @@ impl TestContext {
                 job_id TEXT PRIMARY KEY,
                 status TEXT NOT NULL CHECK (status IN ('Completed', 'Pending', 'Failed')),
                 result JSONB, -- Stores dynamic JSON responses
-                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                result_compressed BYTEA,
+                result_encoding TEXT NOT NULL DEFAULT 'json' CHECK (result_encoding IN ('json', 'gzip')),
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
             )
             "#,
         )
@@ -52,11 +73,28 @@ impl TestContext {
         .await
         .expect("Failed to create job_requests table");
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS idempotency_keys (
+                idempotency_key TEXT PRIMARY KEY,
+                job_id TEXT NOT NULL,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .expect("Failed to create idempotency_keys table");
+
         // Create a single db connection for use by both involved db
         let db = Arc::new(DbConnection { pool: pool.clone() });
         let offchain_processor_db = Arc::new(OffchainProcessorDbConnection::new(db).await.unwrap());
         let app_state = AppState {
             offchain_processor_db: offchain_processor_db.clone(),
+            allowed_program_ids: None,
+            idempotency_key_ttl: DEFAULT_IDEMPOTENCY_KEY_TTL,
+            max_clock_skew: DEFAULT_MAX_CLOCK_SKEW,
+            canary: None,
         };
 
         Self {
@@ -66,6 +104,36 @@ impl TestContext {
         }
     }
 
+    /// Only allows the given identifiers to be proven.
+    pub fn with_allowed_program_ids(mut self, ids: &[&str]) -> Self {
+        self.app_state.allowed_program_ids =
+            Some(Arc::new(ids.iter().map(|id| id.to_string()).collect()));
+        self
+    }
+
+    /// Remembers idempotency keys for `ttl` instead of the default.
+    pub const fn with_idempotency_key_ttl(mut self, ttl: Duration) -> Self {
+        self.app_state.idempotency_key_ttl = ttl;
+        self
+    }
+
+    /// Allows request timestamps up to `max_clock_skew` in the future instead of the default.
+    pub const fn with_max_clock_skew(mut self, max_clock_skew: Duration) -> Self {
+        self.app_state.max_clock_skew = max_clock_skew;
+        self
+    }
+
+    /// Gzips job results written from now on. Results already stored are left as they are.
+    pub async fn with_compressed_results(mut self) -> Self {
+        let db = OffchainProcessorDbConnection::new(self.offchain_processor_db.db_connection())
+            .await
+            .unwrap()
+            .with_compress_results(true);
+        self.offchain_processor_db = Arc::new(db);
+        self.app_state.offchain_processor_db = self.offchain_processor_db.clone();
+        self
+    }
+
     /// Creates a new job request with a given status.
     pub async fn create_job(&self, job_id: &str, status: JobStatus) {
         create_job_request(self.offchain_processor_db.clone(), job_id, status)
@@ -73,6 +141,17 @@ impl TestContext {
             .expect("Failed to create job request");
     }
 
+    /// Stores a typed result for an existing job.
+    pub async fn store_job_result(&self, job_id: &str, status: JobStatus, result: &JobResult) {
+        update_job_result(self.offchain_processor_db.clone(), job_id, status, result)
+            .await
+            .expect("Failed to store job result");
+    }
+
+    pub async fn get_job_result(&self, job_id: &str) -> Result<Option<JobResult>, sqlx::Error> {
+        get_job_result(self.offchain_processor_db.clone(), job_id).await
+    }
+
     pub async fn get_job_status(
         &self,
         job_id: &str,
@@ -84,12 +163,67 @@ impl TestContext {
         .await
     }
 
+    /// Opens the status event stream of a job, as `GET /job/{job_id}/events` would.
+    pub async fn get_job_events(&self, job_id: &str) -> Response {
+        get_job_events(
+            State(self.app_state.clone()),
+            axum::extract::Path(job_id.to_string()),
+        )
+        .await
+    }
+
+    /// Requests the stored result of a job, as `GET /job/{job_id}/result` would.
+    pub async fn get_result(&self, job_id: &str) -> (StatusCode, Json<GetJobResultResponseEnum>) {
+        get_result(
+            State(self.app_state.clone()),
+            axum::extract::Path(job_id.to_string()),
+        )
+        .await
+    }
+
+    pub async fn get_jobs_summary(&self) -> (StatusCode, Json<JobsSummaryResponseEnum>) {
+        get_jobs_summary(State(self.app_state.clone())).await
+    }
+
+    /// Lists jobs with the given paging and status filter.
+    pub async fn list_jobs(&self, query: ListJobsQuery) -> (StatusCode, Json<JobListResponseEnum>) {
+        list_jobs(State(self.app_state.clone()), Query(query)).await
+    }
+
     /// Sends a pricing data request and returns the status and response.
     pub async fn get_pricing_data(
         &self,
         payload: PitchLakeJobRequest,
     ) -> (StatusCode, Json<JobResponse>) {
-        get_pricing_data(State(self.app_state.clone()), Json(payload)).await
+        get_pricing_data(
+            State(self.app_state.clone()),
+            HeaderMap::new(),
+            Json(payload),
+        )
+        .await
+    }
+
+    /// Sends a pricing data request with an `Idempotency-Key` header.
+    pub async fn get_pricing_data_with_idempotency_key(
+        &self,
+        payload: PitchLakeJobRequest,
+        idempotency_key: &str,
+    ) -> (StatusCode, Json<JobResponse>) {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            IDEMPOTENCY_KEY_HEADER,
+            HeaderValue::from_str(idempotency_key).unwrap(),
+        );
+        get_pricing_data(State(self.app_state.clone()), headers, Json(payload)).await
+    }
+
+    /// Returns how a job's result is stored, read straight from the table.
+    pub async fn result_encoding(&self, job_id: &str) -> String {
+        sqlx::query_scalar("SELECT result_encoding FROM job_requests WHERE job_id = $1")
+            .bind(job_id)
+            .fetch_one(&self.offchain_processor_db.db_connection().pool)
+            .await
+            .expect("Failed to read result encoding")
     }
 
     pub async fn create_job_with_result(
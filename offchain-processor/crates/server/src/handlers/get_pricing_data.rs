@@ -1,29 +1,43 @@
 use db_access::OffchainProcessorDbConnection;
 use dotenv::dotenv;
+use std::collections::HashSet;
 use std::env;
 use std::sync::Arc;
 
+use crate::handlers::errors::{report_internal_error, INTERNAL_ERROR_MESSAGE};
+use crate::program_id::{parse_program_id, program_id_to_felt};
 use crate::types::PitchLakeJobRequestParams;
-use crate::types::{JobResponse, PitchLakeJobRequest};
-use crate::AppState;
+use crate::types::{
+    FieldError, JobResponse, PitchLakeJobRequest, ProvingJobRequest, ProvingServiceResponse,
+    ProvingServiceStatus,
+};
+use crate::{AppState, CanaryRouting};
 use axum::{
     extract::{Json, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
 };
 use db_access::{
     models::JobStatus,
-    queries::{create_job_request, get_job_request, update_job_status},
+    queries::{
+        create_job_request, get_job_request, release_idempotency_key, reserve_idempotency_key,
+        update_job_result, update_job_status,
+    },
 };
 use eyre::{eyre, Result};
+use fossil_validation::{window_errors, Window};
 use reqwest::Client;
-use serde_json::json;
+use starknet_types_core::felt::Felt;
+use std::future::Future;
+use std::time::Duration;
 use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
 #[cfg(not(test))]
 use uuid::Uuid;
 
 // Main handler function
 pub async fn get_pricing_data(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<PitchLakeJobRequest>,
 ) -> (StatusCode, Json<JobResponse>) {
     let identifiers = payload.identifiers.join(",");
@@ -40,15 +54,42 @@ pub async fn get_pricing_data(
 
     tracing::info!("Received pricing data request. {}", context);
 
-    if let Err((status, response)) = validate_request(&payload) {
+    if let Err((status, response)) = validate_request(
+        &payload,
+        state.allowed_program_ids.as_deref(),
+        state.max_clock_skew,
+    ) {
         tracing::warn!("Invalid request: {:?}. {}", response, context);
         return (status, Json(response));
     }
 
+    let idempotency_key = match idempotency_key(&headers) {
+        Ok(idempotency_key) => idempotency_key,
+        Err(response) => {
+            tracing::warn!("Invalid request: {:?}. {}", response, context);
+            return (StatusCode::BAD_REQUEST, Json(response));
+        }
+    };
+
     let job_id = generate_job_id(&payload.identifiers, &payload.params);
 
     tracing::info!("Generated job_id: {}. {}", job_id, context);
 
+    if let Some(idempotency_key) = &idempotency_key {
+        match reserve_or_replay(&state, idempotency_key, &job_id).await {
+            Ok(Some(response)) => {
+                tracing::info!(
+                    "Answering with the job of idempotency key {}. {}",
+                    idempotency_key,
+                    context
+                );
+                return response;
+            }
+            Ok(None) => {}
+            Err(e) => return internal_server_error(e, String::new()),
+        }
+    }
+
     match get_job_request(state.offchain_processor_db.clone(), &job_id).await {
         Ok(Some(job_request)) => {
             tracing::info!(
@@ -60,28 +101,230 @@ pub async fn get_pricing_data(
         }
         Ok(None) => {
             tracing::info!("Creating new job request. {}", context);
-            handle_new_job_request(&state, job_id, payload).await
+            let response = handle_new_job_request(&state, job_id.clone(), payload).await;
+            if let Some(idempotency_key) = &idempotency_key {
+                if response.0 != StatusCode::CREATED {
+                    release_reservation(&state, idempotency_key, &job_id).await;
+                }
+            }
+            response
         }
         Err(e) => {
             tracing::error!("Database error: {}. {}", e, context);
+            if let Some(idempotency_key) = &idempotency_key {
+                release_reservation(&state, idempotency_key, &job_id).await;
+            }
             internal_server_error(e, job_id)
         }
     }
 }
 
-// Helper to validate the request
-fn validate_request(payload: &PitchLakeJobRequest) -> Result<(), (StatusCode, JobResponse)> {
+// Header a client sets so that retrying a request doesn't create a second job
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+// Reads the idempotency key of a request, if it was sent with one
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, JobResponse> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    match value.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => {
+            Ok(Some(key.to_string()))
+        }
+        _ => Err(JobResponse::new(
+            String::new(),
+            Some(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters.",
+                MAX_IDEMPOTENCY_KEY_LEN
+            )),
+            None,
+        )),
+    }
+}
+
+// Reserves an idempotency key for the job about to be created. A retried request whose key is
+// already reserved is answered with the job it was reserved for, or with a conflict while that
+// job is still being created.
+async fn reserve_or_replay(
+    state: &AppState,
+    idempotency_key: &str,
+    job_id: &str,
+) -> Result<Option<(StatusCode, Json<JobResponse>)>, sqlx::Error> {
+    let db = state.offchain_processor_db.clone();
+    let Some(reserved_job_id) = reserve_idempotency_key(
+        db.clone(),
+        idempotency_key,
+        job_id,
+        state.idempotency_key_ttl,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let response = match get_job_request(db, &reserved_job_id).await? {
+        Some(job) => (
+            StatusCode::OK,
+            Json(JobResponse::new(
+                job.job_id,
+                Some("Request already received with this idempotency key.".to_string()),
+                Some(job.status),
+            )),
+        ),
+        None => (
+            StatusCode::CONFLICT,
+            Json(JobResponse::new(
+                reserved_job_id,
+                Some("Request with this idempotency key is still being processed.".to_string()),
+                None,
+            )),
+        ),
+    };
+    Ok(Some(response))
+}
+
+// Releases the idempotency key reserved for a job that couldn't be created. Failing to release
+// it only makes retries conflict until the key expires.
+async fn release_reservation(state: &AppState, idempotency_key: &str, job_id: &str) {
+    if let Err(e) =
+        release_idempotency_key(state.offchain_processor_db.clone(), idempotency_key, job_id).await
+    {
+        tracing::error!(
+            "Failed to release idempotency key {} of job {}: {}",
+            idempotency_key,
+            job_id,
+            e
+        );
+    }
+}
+
+// Helper to validate the request. Every invalid field is reported at once, with a message
+// joining theirs.
+fn validate_request(
+    payload: &PitchLakeJobRequest,
+    allowed_program_ids: Option<&HashSet<String>>,
+    max_clock_skew: Duration,
+) -> Result<(), (StatusCode, JobResponse)> {
+    let latest_timestamp = chrono::Utc::now().timestamp() + max_clock_skew.as_secs() as i64;
+    let mut errors = Vec::new();
+
     if payload.identifiers.is_empty() {
+        errors.push(FieldError::new(
+            "identifiers",
+            "Identifiers cannot be empty.",
+        ));
+    }
+    let mut program_ids = Vec::new();
+    for identifier in &payload.identifiers {
+        // The verifier stores program ids as felts, so the identifier must encode to one
+        match program_id_to_felt(identifier) {
+            Ok(program_id) => program_ids.push((identifier, program_id)),
+            Err(e) => errors.push(FieldError::new(
+                "identifiers",
+                format!("Invalid identifier: {}.", e),
+            )),
+        }
+    }
+    errors.extend(validate_time_ranges(&payload.params, latest_timestamp));
+    errors.extend(check_timestamp(
+        "client_info.timestamp",
+        &[payload.client_info.timestamp],
+        latest_timestamp,
+    ));
+    if let Err(e) = parse_hex_felt(&payload.client_info.vault_address) {
+        errors.push(FieldError::new(
+            "client_info.vault_address",
+            format!("Invalid vault address: {}.", e),
+        ));
+    }
+
+    if !errors.is_empty() {
+        let message = errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
         return Err((
             StatusCode::BAD_REQUEST,
-            JobResponse::new(
-                String::new(),
-                Some("Identifiers cannot be empty.".to_string()),
-                None,
-            ),
+            JobResponse {
+                errors,
+                ..JobResponse::new(String::new(), Some(message), None)
+            },
         ));
     }
-    validate_time_ranges(&payload.params)
+
+    for (identifier, program_id) in program_ids {
+        if let Some(allowed_program_ids) = allowed_program_ids {
+            let allowed = allowed_program_ids
+                .iter()
+                .any(|allowed| parse_program_id(allowed).is_ok_and(|felt| felt == program_id));
+            if !allowed {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    JobResponse::new(
+                        String::new(),
+                        Some(format!("Identifier {} is not allowed.", identifier)),
+                        None,
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Checks the timestamps of a field are set and no later than `latest_timestamp`
+fn check_timestamp(field: &str, timestamps: &[i64], latest_timestamp: i64) -> Option<FieldError> {
+    if timestamps.iter().any(|&timestamp| timestamp <= 0) {
+        return Some(FieldError::new(
+            field,
+            format!("Timestamps of {} must be positive.", field),
+        ));
+    }
+    if timestamps
+        .iter()
+        .any(|&timestamp| timestamp > latest_timestamp)
+    {
+        return Some(FieldError::new(
+            field,
+            format!("Timestamps of {} must not be in the future.", field),
+        ));
+    }
+    None
+}
+
+// Parses a `0x`-prefixed hex felt, such as a contract address, rejecting values past the field
+// prime instead of reducing them
+fn parse_hex_felt(value: &str) -> Result<Felt, String> {
+    let Some(digits) = value.strip_prefix("0x") else {
+        return Err(format!("{} is not 0x-prefixed", value));
+    };
+    if digits.is_empty() || digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "{} is not a hex number of at most 64 digits",
+            value
+        ));
+    }
+
+    let felt = Felt::from_hex(value).map_err(|e| format!("{}: {}", value, e))?;
+    let canonical = digits.trim_start_matches('0').to_ascii_lowercase();
+    if felt.to_hex_string()
+        != format!(
+            "0x{}",
+            if canonical.is_empty() {
+                "0"
+            } else {
+                &canonical
+            }
+        )
+    {
+        return Err(format!("{} is not below the field prime", value));
+    }
+    Ok(felt)
 }
 
 // Helper to generate a job ID
@@ -150,27 +393,30 @@ async fn handle_new_job_request(
     {
         Ok(_) => {
             tracing::info!("New job request registered and processing initiated.");
-            let offchain_processor_db_clone = state.offchain_processor_db.clone();
-            let job_id_clone = job_id.clone();
-            let handle = Handle::current();
-
-            tokio::task::spawn_blocking(move || {
-                handle.block_on(process_job(
-                    offchain_processor_db_clone,
-                    job_id_clone,
+            let offchain_processor_db = state.offchain_processor_db.clone();
+            spawn_job_processing(
+                offchain_processor_db.clone(),
+                job_id.clone(),
+                process_job(
+                    offchain_processor_db,
+                    select_proving_service_url(
+                        proving_service_url(),
+                        state.canary.as_deref(),
+                        &job_id,
+                        &payload,
+                    ),
+                    job_id.clone(),
                     payload,
-                ));
-            });
+                ),
+            );
 
             (
                 StatusCode::CREATED,
-                Json(JobResponse {
-                    job_id: job_id.clone(),
-                    message: Some(
-                        "New job request registered and processing initiated.".to_string(),
-                    ),
-                    status: Some(JobStatus::Pending),
-                }),
+                Json(JobResponse::new(
+                    job_id.clone(),
+                    Some("New job request registered and processing initiated.".to_string()),
+                    Some(JobStatus::Pending),
+                )),
             )
         }
         Err(e) => internal_server_error(e, job_id),
@@ -193,17 +439,22 @@ async fn reprocess_failed_job(
     {
         return internal_server_error(e, job_id);
     }
-    let offchain_processor_db_clone = state.offchain_processor_db.clone();
-    let job_id_clone = job_id.clone();
-    let handle = Handle::current();
-
-    tokio::task::spawn_blocking(move || {
-        handle.block_on(process_job(
-            offchain_processor_db_clone,
-            job_id_clone,
+    let offchain_processor_db = state.offchain_processor_db.clone();
+    spawn_job_processing(
+        offchain_processor_db.clone(),
+        job_id.clone(),
+        process_job(
+            offchain_processor_db,
+            select_proving_service_url(
+                proving_service_url(),
+                state.canary.as_deref(),
+                &job_id,
+                &payload,
+            ),
+            job_id.clone(),
             payload,
-        ));
-    });
+        ),
+    );
 
     job_response(
         StatusCode::OK,
@@ -225,22 +476,97 @@ fn job_response(
     )
 }
 
-// Handle internal server errors
+// Handle internal server errors. The details are only logged, the client gets an id to
+// correlate the response with the logs.
 fn internal_server_error(error: sqlx::Error, job_id: String) -> (StatusCode, Json<JobResponse>) {
-    tracing::error!("Internal server error: {:?}", error);
+    let error_id = report_internal_error(&error);
     (
         StatusCode::INTERNAL_SERVER_ERROR,
-        Json(JobResponse::new(
-            job_id,
-            Some(format!("An error occurred: {}", error)),
-            None,
-        )),
+        Json(JobResponse {
+            error_id: Some(error_id),
+            ..JobResponse::new(job_id, Some(INTERNAL_ERROR_MESSAGE.to_string()), None)
+        }),
     )
 }
 
+// Message stored on jobs the proving service refused because proof generation is disabled
+pub const PROOFS_DISABLED_MESSAGE: &str = "Proof generation is disabled on the proving service";
+
+// Get proving service URL from environment variables, with a default value
+fn proving_service_url() -> String {
+    dotenv().ok();
+    env::var("PROVING_SERVICE_URL").unwrap_or_else(|_| "http://127.0.0.1:3000".to_string())
+}
+
+// Pick the proving service a job is sent to: the canary for requests flagged `canary` and for
+// its rollout share of the others, `primary_url` otherwise. The share is picked by job id, so a
+// reprocessed job goes to the same deployment.
+fn select_proving_service_url(
+    primary_url: String,
+    canary: Option<&CanaryRouting>,
+    job_id: &str,
+    payload: &PitchLakeJobRequest,
+) -> String {
+    match canary {
+        Some(canary)
+            if payload.canary || rollout_bucket(job_id) < u64::from(canary.rollout_percent) =>
+        {
+            tracing::info!("Routing job {} to the canary proving service", job_id);
+            canary.url.clone()
+        }
+        _ => primary_url,
+    }
+}
+
+// Stable bucket from 0 to 99 of a job id (FNV-1a), unlike the std hasher which is seeded per
+// process
+fn rollout_bucket(job_id: &str) -> u64 {
+    let hash = job_id.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    hash % 100
+}
+
+// Message stored on jobs whose processing panicked
+pub const JOB_PANICKED_MESSAGE: &str = "Job processing panicked unexpectedly";
+
+// Run the job processing on a blocking thread. A supervisor task awaits it so that a panic
+// marks the job as failed instead of leaving it pending forever.
+fn spawn_job_processing<F>(
+    offchain_processor_db: Arc<OffchainProcessorDbConnection>,
+    job_id: String,
+    processing: F,
+) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let handle = Handle::current();
+    let processing = tokio::task::spawn_blocking(move || handle.block_on(processing));
+
+    tokio::spawn(async move {
+        let error = match processing.await {
+            Err(e) if e.is_panic() => e,
+            _ => return,
+        };
+        tracing::error!("Processing of job {} panicked: {}", job_id, error);
+
+        if let Err(e) = update_job_status(
+            offchain_processor_db,
+            &job_id,
+            JobStatus::Failed,
+            Some(serde_json::json!({ "error": JOB_PANICKED_MESSAGE })),
+        )
+        .await
+        {
+            tracing::error!("Failed to mark panicked job {} as failed: {}", job_id, e);
+        }
+    })
+}
+
 // Process the job and trigger request to the proving service
 async fn process_job(
     offchain_processor_db: Arc<OffchainProcessorDbConnection>,
+    proving_service_url: String,
     job_id: String,
     payload: PitchLakeJobRequest,
 ) {
@@ -258,15 +584,28 @@ async fn process_job(
     tracing::info!("Starting job processing. {}", context);
     tracing::debug!("Payload received: {:?}. {}", payload, context);
 
-    let job_result = match call_proving_service(&job_id, &payload).await {
-        Ok(result) => {
+    let job_result = match call_proving_service(&proving_service_url, &job_id, &payload).await {
+        Ok(response) if response.status == ProvingServiceStatus::Disabled => {
+            tracing::error!("{}. {}", PROOFS_DISABLED_MESSAGE, context);
+            let _ = update_job_status(
+                offchain_processor_db.clone(),
+                &job_id,
+                JobStatus::Failed,
+                Some(serde_json::json!({
+                    "error": PROOFS_DISABLED_MESSAGE
+                })),
+            )
+            .await;
+            false
+        }
+        Ok(response) => {
             tracing::info!("Proving service response received. {}", context);
 
-            if let Err(e) = update_job_status(
+            if let Err(e) = update_job_result(
                 offchain_processor_db.clone(),
                 &job_id,
                 JobStatus::Completed,
-                Some(result.clone()),
+                &response.result,
             )
             .await
             {
@@ -305,32 +644,13 @@ async fn process_job(
 
 // Call the proving service API
 async fn call_proving_service(
+    proving_service_url: &str,
     job_id: &str,
     payload: &PitchLakeJobRequest,
-) -> Result<serde_json::Value, eyre::Error> {
-    dotenv().ok();
-
-    // Get proving service URL from environment variables, with a default value
-    let proving_service_url =
-        env::var("PROVING_SERVICE_URL").unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
-
+) -> Result<ProvingServiceResponse, eyre::Error> {
     let client = Client::new();
 
-    let api_payload = json!({
-        "job_group_id": job_id,
-        "twap": {
-            "start_timestamp": payload.params.twap.0,
-            "end_timestamp": payload.params.twap.1
-        },
-        "reserve_price": {
-            "start_timestamp": payload.params.reserve_price.0,
-            "end_timestamp": payload.params.reserve_price.1
-        },
-        "max_return": {
-            "start_timestamp": payload.params.volatility.0,
-            "end_timestamp": payload.params.volatility.1
-        }
-    });
+    let api_payload = to_proving_request(job_id, payload);
 
     tracing::debug!("Sending request to proving service: {:?}", api_payload);
 
@@ -350,7 +670,7 @@ async fn call_proving_service(
     }
 
     let result = response
-        .json::<serde_json::Value>()
+        .json::<ProvingServiceResponse>()
         .await
         .map_err(|e| eyre!("Failed to parse response from proving service: {}", e))?;
 
@@ -359,37 +679,57 @@ async fn call_proving_service(
     Ok(result)
 }
 
-// Validate the provided time ranges
+// Map a PitchLake job request onto the proving service's job request.
+// The volatility range is proven by the proving service's max return job.
+pub fn to_proving_request(job_id: &str, payload: &PitchLakeJobRequest) -> ProvingJobRequest {
+    ProvingJobRequest {
+        job_group_id: job_id.to_string(),
+        twap: payload.params.twap.into(),
+        reserve_price: payload.params.reserve_price.into(),
+        max_return: payload.params.volatility.into(),
+        tag: payload.tag.clone(),
+    }
+}
+
+// Validate the provided time ranges. Volatility is validated as the max return window, as
+// that is the job proving it.
 fn validate_time_ranges(
     params: &PitchLakeJobRequestParams,
-) -> Result<(), (StatusCode, JobResponse)> {
-    let validations = [
-        ("TWAP", params.twap),
-        ("Volatility", params.volatility),
-        ("Reserve Price", params.reserve_price),
-    ];
-
-    for (name, (start, end)) in &validations {
-        if start >= end {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                JobResponse::new(
-                    String::new(),
-                    Some(format!("Invalid time range for {} calculation.", name)),
-                    None,
-                ),
-            ));
-        }
+    latest_timestamp: i64,
+) -> Vec<FieldError> {
+    let mut errors = [
+        ("params.twap", params.twap),
+        ("params.volatility", params.volatility),
+        ("params.reserve_price", params.reserve_price),
+    ]
+    .into_iter()
+    .filter_map(|(field, (start, end))| check_timestamp(field, &[start, end], latest_timestamp))
+    .collect::<Vec<_>>();
+
+    for e in window_errors(params.twap, params.reserve_price, params.volatility) {
+        let field = match e.window() {
+            Window::Twap => "params.twap",
+            Window::ReservePrice => "params.reserve_price",
+            Window::MaxReturn => "params.volatility",
+        };
+        errors.push(FieldError::new(field, e.to_string()));
     }
-    Ok(())
+
+    errors
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::handlers::fixtures::TestContext;
-    use crate::types::{ClientInfo, PitchLakeJobRequest, PitchLakeJobRequestParams};
+    use crate::types::{
+        ClientInfo, PitchLakeJobRequest, PitchLakeJobRequestParams, ProvingTimeRange,
+    };
     use axum::http::StatusCode;
+    use fossil_validation::MAX_WINDOW_SECS;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_get_pricing_data_new_job() {
@@ -398,15 +738,17 @@ mod tests {
         let payload = PitchLakeJobRequest {
             identifiers: vec!["test-id".to_string()],
             params: PitchLakeJobRequestParams {
-                twap: (0, 100),
-                volatility: (0, 100),
-                reserve_price: (0, 100),
+                twap: (1, 100),
+                volatility: (1, 100),
+                reserve_price: (1, 100),
             },
             client_info: ClientInfo {
                 client_address: "0x123".to_string(),
                 vault_address: "0x456".to_string(),
-                timestamp: 0,
+                timestamp: 1,
             },
+            tag: None,
+            canary: false,
         };
 
         let (status, Json(response)) = ctx.get_pricing_data(payload).await;
@@ -426,15 +768,17 @@ mod tests {
         let payload = PitchLakeJobRequest {
             identifiers: vec!["test-id".to_string()],
             params: PitchLakeJobRequestParams {
-                twap: (0, 100),
-                volatility: (0, 100),
-                reserve_price: (0, 100),
+                twap: (1, 100),
+                volatility: (1, 100),
+                reserve_price: (1, 100),
             },
             client_info: ClientInfo {
                 client_address: "0x123".to_string(),
                 vault_address: "0x456".to_string(),
-                timestamp: 0,
+                timestamp: 1,
             },
+            tag: None,
+            canary: false,
         };
 
         let job_id = generate_job_id(&payload.identifiers, &payload.params);
@@ -457,15 +801,17 @@ mod tests {
         let payload = PitchLakeJobRequest {
             identifiers: vec!["test-id".to_string()],
             params: PitchLakeJobRequestParams {
-                twap: (0, 100),
-                volatility: (0, 100),
-                reserve_price: (0, 100),
+                twap: (1, 100),
+                volatility: (1, 100),
+                reserve_price: (1, 100),
             },
             client_info: ClientInfo {
                 client_address: "0x123".to_string(),
                 vault_address: "0x456".to_string(),
-                timestamp: 0,
+                timestamp: 1,
             },
+            tag: None,
+            canary: false,
         };
 
         let job_id = generate_job_id(&payload.identifiers, &payload.params);
@@ -488,15 +834,17 @@ mod tests {
         let payload = PitchLakeJobRequest {
             identifiers: vec!["test-id".to_string()],
             params: PitchLakeJobRequestParams {
-                twap: (0, 100),
-                volatility: (0, 100),
-                reserve_price: (0, 100),
+                twap: (1, 100),
+                volatility: (1, 100),
+                reserve_price: (1, 100),
             },
             client_info: ClientInfo {
                 client_address: "0x123".to_string(),
                 vault_address: "0x456".to_string(),
-                timestamp: 0,
+                timestamp: 1,
             },
+            tag: None,
+            canary: false,
         };
 
         let job_id = generate_job_id(&payload.identifiers, &payload.params);
@@ -519,15 +867,17 @@ mod tests {
         let payload = PitchLakeJobRequest {
             identifiers: vec!["test-id".to_string()],
             params: PitchLakeJobRequestParams {
-                twap: (100, 0), // Invalid range
-                volatility: (0, 100),
-                reserve_price: (0, 100),
+                twap: (100, 1), // Invalid range
+                volatility: (1, 100),
+                reserve_price: (1, 100),
             },
             client_info: ClientInfo {
                 client_address: "0x123".to_string(),
                 vault_address: "0x456".to_string(),
-                timestamp: 0,
+                timestamp: 1,
             },
+            tag: None,
+            canary: false,
         };
 
         let (status, Json(response)) = ctx.get_pricing_data(payload).await;
@@ -538,4 +888,600 @@ mod tests {
             "Invalid time range for TWAP calculation."
         );
     }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_reports_every_invalid_field() {
+        let ctx = TestContext::new().await;
+        let tomorrow = chrono::Utc::now().timestamp() + 24 * 3600;
+
+        let mut payload = pricing_request("test-id");
+        payload.params.twap = (tomorrow - 3600, tomorrow);
+        payload.params.reserve_price = (1, tomorrow);
+        payload.params.volatility = (1, tomorrow);
+        payload.client_info.vault_address = "0xnot_hex".to_string();
+
+        let (status, Json(response)) = ctx.get_pricing_data(payload).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let fields: Vec<_> = response.errors.iter().map(|e| e.field.as_str()).collect();
+        assert!(fields.contains(&"params.twap"));
+        assert!(fields.contains(&"params.reserve_price"));
+        assert!(fields.contains(&"params.volatility"));
+        assert!(fields.contains(&"client_info.vault_address"));
+        assert!(response.errors.contains(&FieldError::new(
+            "params.twap",
+            "Timestamps of params.twap must not be in the future."
+        )));
+        assert!(response.errors.contains(&FieldError::new(
+            "params.reserve_price",
+            format!(
+                "Time range for Reserve Price calculation exceeds the maximum of {} seconds.",
+                MAX_WINDOW_SECS
+            )
+        )));
+        assert!(response.errors.contains(&FieldError::new(
+            "params.volatility",
+            format!(
+                "Time range for Max Return calculation exceeds the maximum of {} seconds.",
+                MAX_WINDOW_SECS
+            )
+        )));
+        assert!(response
+            .message
+            .unwrap_or_default()
+            .contains("Invalid vault address: 0xnot_hex is not a hex number"));
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_rejects_zero_timestamps() {
+        let ctx = TestContext::new().await;
+
+        let mut payload = pricing_request("test-id");
+        payload.client_info.timestamp = 0;
+
+        let (status, Json(response)) = ctx.get_pricing_data(payload).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.errors,
+            [FieldError::new(
+                "client_info.timestamp",
+                "Timestamps of client_info.timestamp must be positive."
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_allows_timestamps_within_clock_skew() {
+        let ctx = TestContext::new()
+            .await
+            .with_max_clock_skew(Duration::from_secs(600));
+
+        let mut payload = pricing_request("test-id");
+        payload.client_info.timestamp = chrono::Utc::now().timestamp() + 60;
+        let (status, _) = ctx.get_pricing_data(payload).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let mut payload = pricing_request("test-id");
+        payload.client_info.timestamp = chrono::Utc::now().timestamp() + 3600;
+        let (status, Json(response)) = ctx.get_pricing_data(payload).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.errors[0].field, "client_info.timestamp");
+    }
+
+    #[test]
+    fn test_parse_hex_felt() {
+        assert_eq!(parse_hex_felt("0x456"), Ok(Felt::from(0x456)));
+        assert_eq!(parse_hex_felt("0x00ABC"), Ok(Felt::from(0xabc)));
+        assert_eq!(parse_hex_felt("0x0"), Ok(Felt::ZERO));
+        assert!(parse_hex_felt("456").is_err());
+        assert!(parse_hex_felt("0x").is_err());
+        assert!(parse_hex_felt("0xg").is_err());
+        assert!(parse_hex_felt(&format!("0x{}", "1".repeat(65))).is_err());
+        // The field prime itself
+        assert!(parse_hex_felt(
+            "0x800000000000011000000000000000000000000000000000000000000000001"
+        )
+        .is_err());
+    }
+
+    fn pricing_request(identifier: &str) -> PitchLakeJobRequest {
+        PitchLakeJobRequest {
+            identifiers: vec![identifier.to_string()],
+            params: PitchLakeJobRequestParams {
+                twap: (1, 100),
+                volatility: (1, 100),
+                reserve_price: (1, 100),
+            },
+            client_info: ClientInfo {
+                client_address: "0x123".to_string(),
+                vault_address: "0x456".to_string(),
+                timestamp: 1,
+            },
+            tag: None,
+            canary: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_allowed_program_id() {
+        let ctx = TestContext::new()
+            .await
+            .with_allowed_program_ids(&["allowed-id"]);
+
+        let (status, _) = ctx.get_pricing_data(pricing_request("allowed-id")).await;
+
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_disallowed_program_id() {
+        let ctx = TestContext::new()
+            .await
+            .with_allowed_program_ids(&["allowed-id"]);
+
+        let (status, Json(response)) = ctx.get_pricing_data(pricing_request("other-id")).await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(
+            response.message.unwrap_or_default(),
+            "Identifier other-id is not allowed."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_allowlist_matches_program_id_felt() {
+        // 'PITCHLAKE_V1' as the verifier stores it
+        let ctx = TestContext::new()
+            .await
+            .with_allowed_program_ids(&["0x50495443484c414b455f5631"]);
+
+        let (status, _) = ctx.get_pricing_data(pricing_request("PITCHLAKE_V1")).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, _) = ctx.get_pricing_data(pricing_request("PITCHLAKE_V2")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_rejects_identifier_not_encodable_as_felt() {
+        let ctx = TestContext::new().await;
+
+        let (status, Json(response)) = ctx
+            .get_pricing_data(pricing_request("a-program-id-too-long-for-one-felt"))
+            .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.message.unwrap_or_default(),
+            "Invalid identifier: Program id a-program-id-too-long-for-one-felt is longer than 31 characters."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_without_allowlist_allows_any_program_id() {
+        let ctx = TestContext::new().await;
+
+        let (status, _) = ctx
+            .get_pricing_data(pricing_request("any-program-id"))
+            .await;
+
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_same_idempotency_key_returns_same_job() {
+        let ctx = TestContext::new().await;
+
+        let (status, Json(first)) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("first-id"), "key-a")
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // A retry answers with the job the key created, even if its body changed
+        let (status, Json(retry)) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("second-id"), "key-a")
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(retry.job_id, first.job_id);
+        assert_eq!(
+            retry.message.unwrap_or_default(),
+            "Request already received with this idempotency key."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_different_idempotency_key_creates_new_job() {
+        let ctx = TestContext::new().await;
+
+        let (_, Json(first)) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("first-id"), "key-a")
+            .await;
+        let (status, Json(second)) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("second-id"), "key-b")
+            .await;
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(second.job_id, first.job_id);
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_expired_idempotency_key_is_ignored() {
+        let ctx = TestContext::new()
+            .await
+            .with_idempotency_key_ttl(Duration::ZERO);
+
+        let (_, Json(first)) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("first-id"), "key-a")
+            .await;
+        let (status, Json(second)) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("second-id"), "key-a")
+            .await;
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(second.job_id, first.job_id);
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_concurrent_idempotent_requests_create_one_job() {
+        let ctx = TestContext::new().await;
+
+        let ((first_status, Json(first)), (second_status, Json(second))) = tokio::join!(
+            ctx.get_pricing_data_with_idempotency_key(pricing_request("first-id"), "key-a"),
+            ctx.get_pricing_data_with_idempotency_key(pricing_request("second-id"), "key-a"),
+        );
+
+        // Whichever reserved the key creates the job, the other answers with it
+        let created = [first_status, second_status]
+            .iter()
+            .filter(|status| **status == StatusCode::CREATED)
+            .count();
+        assert_eq!(created, 1);
+        assert_eq!(first.job_id, second.job_id);
+        let jobs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM job_requests")
+            .fetch_one(&ctx.offchain_processor_db.db_connection().pool)
+            .await
+            .unwrap();
+        assert_eq!(jobs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_idempotency_key_keeps_unexpired_entry() {
+        let ctx = TestContext::new().await;
+        let ttl = Duration::from_secs(3600);
+
+        let reserved =
+            reserve_idempotency_key(ctx.offchain_processor_db.clone(), "key-a", "job-1", ttl)
+                .await
+                .unwrap();
+        assert_eq!(reserved, None);
+
+        let reserved =
+            reserve_idempotency_key(ctx.offchain_processor_db.clone(), "key-a", "job-2", ttl)
+                .await
+                .unwrap();
+        assert_eq!(reserved.as_deref(), Some("job-1"));
+
+        // Once released, the key can be reserved for another job
+        release_idempotency_key(ctx.offchain_processor_db.clone(), "key-a", "job-1")
+            .await
+            .unwrap();
+        let reserved =
+            reserve_idempotency_key(ctx.offchain_processor_db.clone(), "key-a", "job-2", ttl)
+                .await
+                .unwrap();
+        assert_eq!(reserved, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_pricing_data_rejects_empty_idempotency_key() {
+        let ctx = TestContext::new().await;
+
+        let (status, _) = ctx
+            .get_pricing_data_with_idempotency_key(pricing_request("first-id"), "")
+            .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_to_proving_request_maps_fields() {
+        let payload = PitchLakeJobRequest {
+            identifiers: vec!["test-id".to_string()],
+            params: PitchLakeJobRequestParams {
+                twap: (100, 200),
+                volatility: (300, 400),
+                reserve_price: (500, 600),
+            },
+            client_info: ClientInfo {
+                client_address: "0x123".to_string(),
+                vault_address: "0x456".to_string(),
+                timestamp: 1,
+            },
+            tag: None,
+            canary: false,
+        };
+
+        let request = to_proving_request("job-123", &payload);
+
+        assert_eq!(request.job_group_id, "job-123");
+        assert_eq!(
+            request.twap,
+            ProvingTimeRange {
+                start_timestamp: 100,
+                end_timestamp: 200,
+            }
+        );
+        assert_eq!(
+            request.reserve_price,
+            ProvingTimeRange {
+                start_timestamp: 500,
+                end_timestamp: 600,
+            }
+        );
+        // Volatility is proven as max return by the proving service
+        assert_eq!(
+            request.max_return,
+            ProvingTimeRange {
+                start_timestamp: 300,
+                end_timestamp: 400,
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_proving_request_serialization() {
+        let payload = PitchLakeJobRequest {
+            identifiers: vec!["test-id".to_string()],
+            params: PitchLakeJobRequestParams {
+                twap: (1, 2),
+                volatility: (3, 4),
+                reserve_price: (5, 6),
+            },
+            client_info: ClientInfo {
+                client_address: "0x123".to_string(),
+                vault_address: "0x456".to_string(),
+                timestamp: 1,
+            },
+            tag: None,
+            canary: false,
+        };
+
+        let request = serde_json::to_value(to_proving_request("job-456", &payload)).unwrap();
+
+        assert_eq!(
+            request,
+            json!({
+                "job_group_id": "job-456",
+                "twap": {"start_timestamp": 1, "end_timestamp": 2},
+                "reserve_price": {"start_timestamp": 5, "end_timestamp": 6},
+                "max_return": {"start_timestamp": 3, "end_timestamp": 4}
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_proving_request_passes_tag_through() {
+        let payload = PitchLakeJobRequest {
+            identifiers: vec!["test-id".to_string()],
+            params: PitchLakeJobRequestParams {
+                twap: (1, 2),
+                volatility: (3, 4),
+                reserve_price: (5, 6),
+            },
+            client_info: ClientInfo {
+                client_address: "0x123".to_string(),
+                vault_address: "0x456".to_string(),
+                timestamp: 1,
+            },
+            tag: Some("client-tag".to_string()),
+            canary: false,
+        };
+
+        let request = to_proving_request("job-789", &payload);
+        assert_eq!(request.tag.as_deref(), Some("client-tag"));
+
+        let request = serde_json::to_value(request).unwrap();
+        assert_eq!(request["tag"], "client-tag");
+    }
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Serves a stand-in proving service answering every job request with `response`
+    async fn mock_proving_service(response: serde_json::Value) -> String {
+        let app = axum::Router::new().route(
+            "/api/job",
+            axum::routing::post(move || async move { Json(response) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_process_job_fails_job_when_proofs_disabled() {
+        let ctx = TestContext::new().await;
+        let job_id = "disabled_job_id";
+        ctx.create_job(job_id, JobStatus::Pending).await;
+
+        let proving_service_url = mock_proving_service(json!({
+            "status": "disabled",
+            "message": "Proof generation is disabled on the proving service",
+            "job_group_id": job_id
+        }))
+        .await;
+
+        process_job(
+            ctx.offchain_processor_db.clone(),
+            proving_service_url,
+            job_id.to_string(),
+            pricing_request("test-id"),
+        )
+        .await;
+
+        let job = get_job_request(ctx.offchain_processor_db.clone(), job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(
+            job.result,
+            Some(json!({ "error": PROOFS_DISABLED_MESSAGE }))
+        );
+    }
+
+    // Serves a stand-in proving service answering every job request as disabled, counting the
+    // requests it receives
+    async fn counting_proving_service() -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = axum::Router::new().route(
+            "/api/job",
+            axum::routing::post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Json(json!({ "status": "disabled", "job_group_id": "job" }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    #[tokio::test]
+    async fn test_canary_request_is_sent_to_canary_proving_service() {
+        let ctx = TestContext::new().await;
+        let (primary_url, primary_hits) = counting_proving_service().await;
+        let (canary_url, canary_hits) = counting_proving_service().await;
+        let canary = CanaryRouting {
+            url: canary_url,
+            rollout_percent: 0,
+        };
+
+        for (job_id, flagged) in [("canary_job_id", true), ("primary_job_id", false)] {
+            ctx.create_job(job_id, JobStatus::Pending).await;
+            let payload = PitchLakeJobRequest {
+                canary: flagged,
+                ..pricing_request("test-id")
+            };
+
+            process_job(
+                ctx.offchain_processor_db.clone(),
+                select_proving_service_url(primary_url.clone(), Some(&canary), job_id, &payload),
+                job_id.to_string(),
+                payload,
+            )
+            .await;
+        }
+
+        assert_eq!(canary_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(primary_hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_select_proving_service_url_follows_rollout() {
+        let primary = || "http://primary".to_string();
+        let canary = |rollout_percent| CanaryRouting {
+            url: "http://canary".to_string(),
+            rollout_percent,
+        };
+        let flagged = PitchLakeJobRequest {
+            canary: true,
+            ..pricing_request("test-id")
+        };
+        let unflagged = pricing_request("test-id");
+
+        // Without a canary, flagged requests go to the primary
+        assert_eq!(
+            select_proving_service_url(primary(), None, "job", &flagged),
+            "http://primary"
+        );
+        assert_eq!(
+            select_proving_service_url(primary(), Some(&canary(0)), "job", &unflagged),
+            "http://primary"
+        );
+        assert_eq!(
+            select_proving_service_url(primary(), Some(&canary(100)), "job", &unflagged),
+            "http://canary"
+        );
+
+        // A partial rollout routes a stable share of job ids to the canary
+        let canary_jobs = (0..1000)
+            .filter(|i| {
+                select_proving_service_url(
+                    primary(),
+                    Some(&canary(10)),
+                    &format!("job_{}", i),
+                    &unflagged,
+                ) == "http://canary"
+            })
+            .count();
+        assert!((50..150).contains(&canary_jobs), "{}", canary_jobs);
+        assert_eq!(rollout_bucket("job_1"), rollout_bucket("job_1"));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job_processing_fails_job() {
+        let ctx = TestContext::new().await;
+        let job_id = "panicking_job_id";
+        ctx.create_job(job_id, JobStatus::Pending).await;
+
+        spawn_job_processing(
+            ctx.offchain_processor_db.clone(),
+            job_id.to_string(),
+            async {
+                panic!("processing blew up");
+            },
+        )
+        .await
+        .unwrap();
+
+        let job = get_job_request(ctx.offchain_processor_db.clone(), job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.result, Some(json!({ "error": JOB_PANICKED_MESSAGE })));
+    }
+
+    #[test]
+    fn test_internal_server_error_hides_details_from_client() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        let raw_error = "relation \"job_requests\" does not exist";
+        let (status, Json(response)) = tracing::subscriber::with_default(subscriber, || {
+            internal_server_error(sqlx::Error::Protocol(raw_error.to_string()), "job-1".into())
+        });
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.job_id, "job-1");
+        let message = response.message.unwrap();
+        assert_eq!(message, INTERNAL_ERROR_MESSAGE);
+        assert!(!message.contains("job_requests"));
+
+        let error_id = response.error_id.expect("Expected an error id");
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains(&error_id));
+        assert!(logs.contains("job_requests"));
+    }
 }
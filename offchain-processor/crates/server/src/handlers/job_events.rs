@@ -0,0 +1,170 @@
+use crate::types::ErrorResponse;
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use db_access::models::JobStatus;
+use db_access::queries::get_job_request;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+/// Data of the `status` events sent on `GET /job/{job_id}/events`.
+#[derive(Debug, Serialize)]
+pub struct JobStatusEvent {
+    pub job_id: String,
+    pub status: JobStatus,
+}
+
+fn status_event(job_id: &str, status: JobStatus) -> Result<Event, axum::Error> {
+    Event::default().event("status").json_data(JobStatusEvent {
+        job_id: job_id.to_string(),
+        status,
+    })
+}
+
+// Sends the current status of the job, then each status change until it reaches a terminal one
+fn status_events(
+    job_id: String,
+    current: JobStatus,
+    receiver: Receiver<JobStatus>,
+) -> impl Stream<Item = Result<Event, axum::Error>> {
+    let first = status_event(&job_id, current);
+    let changes = stream::unfold(
+        (job_id, receiver, current.is_terminal()),
+        |(job_id, mut receiver, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                match receiver.recv().await {
+                    Ok(status) => {
+                        let event = status_event(&job_id, status);
+                        return Some((event, (job_id, receiver, status.is_terminal())));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "Skipped {} status changes of job_id {} for a slow subscriber",
+                            skipped,
+                            job_id
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    stream::once(async { first }).chain(changes)
+}
+
+// Streams the status of a job as Server-Sent Events, so clients don't have to poll the status
+// endpoint. The stream ends once the job is completed or failed.
+pub async fn get_job_events(State(state): State<AppState>, Path(job_id): Path<String>) -> Response {
+    tracing::info!("Streaming status events for job_id: {}", job_id);
+
+    // Subscribe before reading the status, so no change made after the read is missed
+    let receiver = state.offchain_processor_db.job_events().subscribe(&job_id);
+
+    match get_job_request(state.offchain_processor_db.clone(), &job_id).await {
+        Ok(Some(job)) => Sse::new(status_events(job.job_id, job.status, receiver))
+            .keep_alive(KeepAlive::default())
+            .into_response(),
+        Ok(None) => {
+            tracing::info!("Job not found for job_id: {}", job_id);
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "Job not found".to_string(),
+                    error_id: None,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get job status for job_id {}", job_id);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::internal(&e)),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::fixtures::TestContext;
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::response::Response;
+    use db_access::models::JobStatus;
+    use db_access::queries::update_job_status;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    // Reads the events of a stream until it ends, returning the status each one carries
+    async fn read_statuses(response: Response) -> Vec<String> {
+        let body = tokio::time::timeout(
+            Duration::from_secs(5),
+            to_bytes(response.into_body(), usize::MAX),
+        )
+        .await
+        .expect("event stream didn't end")
+        .unwrap();
+
+        String::from_utf8(body.to_vec())
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| {
+                let event: Value = serde_json::from_str(data).unwrap();
+                event["status"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_get_job_events_streams_until_terminal_status() {
+        let ctx = TestContext::new().await;
+        let job_id = "pending_job_id";
+        ctx.create_job(job_id, JobStatus::Pending).await;
+
+        let response = ctx.get_job_events(job_id).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        update_job_status(
+            ctx.offchain_processor_db.clone(),
+            job_id,
+            JobStatus::Completed,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(read_statuses(response).await, ["Pending", "Completed"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_events_ends_after_current_terminal_status() {
+        let ctx = TestContext::new().await;
+        let job_id = "failed_job_id";
+        ctx.create_job(job_id, JobStatus::Failed).await;
+
+        let response = ctx.get_job_events(job_id).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(read_statuses(response).await, ["Failed"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_events_not_found() {
+        let ctx = TestContext::new().await;
+
+        let response = ctx.get_job_events("non_existent_job_id").await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
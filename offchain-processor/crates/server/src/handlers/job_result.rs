@@ -0,0 +1,164 @@
+use crate::types::{ErrorResponse, GetJobResultResponseEnum, JobResponse};
+use crate::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use db_access::models::JobStatus;
+use db_access::queries::get_job_request;
+
+// Returns the stored result of a completed job, so clients don't have to poll the status and
+// then read the result separately
+#[axum::debug_handler]
+pub async fn get_result(
+    State(state): State<AppState>,
+    axum::extract::Path(job_id): axum::extract::Path<String>,
+) -> (StatusCode, Json<GetJobResultResponseEnum>) {
+    tracing::info!("Getting result for job_id: {}", job_id);
+
+    match get_job_request(state.offchain_processor_db, &job_id).await {
+        Ok(Some(job)) => match job.status {
+            JobStatus::Completed => (
+                StatusCode::OK,
+                Json(GetJobResultResponseEnum::Success(
+                    job.result.unwrap_or_default(),
+                )),
+            ),
+            JobStatus::Pending => (
+                StatusCode::ACCEPTED,
+                Json(GetJobResultResponseEnum::Pending(JobResponse::new(
+                    job.job_id,
+                    Some("Job is still being processed".to_string()),
+                    Some(job.status),
+                ))),
+            ),
+            JobStatus::Failed => {
+                // Failed jobs store the reason they failed as `{"error": ...}`
+                let error = job
+                    .result
+                    .as_ref()
+                    .and_then(|result| result.get("error"))
+                    .and_then(|error| error.as_str())
+                    .unwrap_or("Job failed")
+                    .to_string();
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(GetJobResultResponseEnum::Error(ErrorResponse {
+                        error,
+                        error_id: None,
+                    })),
+                )
+            }
+        },
+        Ok(None) => {
+            tracing::info!("Job not found for job_id: {}", job_id);
+            (
+                StatusCode::NOT_FOUND,
+                Json(GetJobResultResponseEnum::Error(ErrorResponse {
+                    error: "Job not found".to_string(),
+                    error_id: None,
+                })),
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to get job result for job_id {}", job_id);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(GetJobResultResponseEnum::Error(ErrorResponse::internal(&e))),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{handlers::fixtures::TestContext, types::GetJobResultResponseEnum};
+    use axum::{http::StatusCode, Json};
+    use db_access::models::JobStatus;
+    use db_access::queries::update_job_status;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_result_completed() {
+        let ctx = TestContext::new().await;
+        let job_id = "completed_job_id";
+        let result = json!({
+            "outputs": { "twap": 12345.67, "reserve_price": 3456.78, "max_return": 0.25 }
+        });
+
+        ctx.create_job_with_result(job_id, JobStatus::Completed, result.clone())
+            .await;
+
+        let (status, Json(response)) = ctx.get_result(job_id).await;
+
+        assert_eq!(status, StatusCode::OK);
+        match response {
+            GetJobResultResponseEnum::Success(stored) => assert_eq!(stored, result),
+            other => panic!("Unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_result_pending() {
+        let ctx = TestContext::new().await;
+        let job_id = "pending_job_id";
+
+        ctx.create_job(job_id, JobStatus::Pending).await;
+
+        let (status, Json(response)) = ctx.get_result(job_id).await;
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        match response {
+            GetJobResultResponseEnum::Pending(pending) => {
+                assert_eq!(pending.job_id, job_id);
+                assert_eq!(pending.status, Some(JobStatus::Pending));
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_result_failed() {
+        let ctx = TestContext::new().await;
+        let job_id = "failed_job_id";
+
+        ctx.create_job(job_id, JobStatus::Failed).await;
+        let (status, Json(response)) = ctx.get_result(job_id).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        match response {
+            GetJobResultResponseEnum::Error(err) => assert_eq!(err.error, "Job failed"),
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        update_job_status(
+            ctx.offchain_processor_db.clone(),
+            job_id,
+            JobStatus::Failed,
+            Some(json!({ "error": "Error calling proving service" })),
+        )
+        .await
+        .unwrap();
+        let (status, Json(response)) = ctx.get_result(job_id).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        match response {
+            GetJobResultResponseEnum::Error(err) => {
+                assert_eq!(err.error, "Error calling proving service")
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_result_not_found() {
+        let ctx = TestContext::new().await;
+
+        let (status, Json(response)) = ctx.get_result("non_existent_job_id").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        match response {
+            GetJobResultResponseEnum::Error(err) => assert_eq!(err.error, "Job not found"),
+            other => panic!("Unexpected response: {:?}", other),
+        }
+    }
+}
@@ -1,10 +1,23 @@
-use crate::types::{ErrorResponse, GetJobStatusResponseEnum, JobResponse};
+use crate::types::{ErrorResponse, GetJobStatusResponseEnum, JobStatusResponse};
 use crate::AppState;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::Json;
+use db_access::models::{JobRequest, JobStatus};
 use db_access::queries::get_job_request;
 
+// Failed jobs store the reason they failed as `{"error": ...}`
+fn failure_reason(job: &JobRequest) -> Option<String> {
+    if job.status != JobStatus::Failed {
+        return None;
+    }
+    job.result
+        .as_ref()
+        .and_then(|result| result.get("error"))
+        .and_then(|error| error.as_str())
+        .map(str::to_string)
+}
+
 #[axum::debug_handler]
 pub async fn get_job_status(
     State(state): State<AppState>,
@@ -17,10 +30,13 @@ pub async fn get_job_status(
             tracing::info!("Found job status: {:?} for job_id: {}", job.status, job_id);
             (
                 StatusCode::OK,
-                Json(GetJobStatusResponseEnum::Success(JobResponse {
+                Json(GetJobStatusResponseEnum::Success(JobStatusResponse {
+                    error: failure_reason(&job),
                     job_id: job.job_id,
                     message: None,
                     status: Some(job.status),
+                    created_at: Some(job.created_at),
+                    updated_at: Some(job.updated_at),
                 })),
             )
         }
@@ -30,16 +46,15 @@ pub async fn get_job_status(
                 StatusCode::NOT_FOUND,
                 Json(GetJobStatusResponseEnum::Error(ErrorResponse {
                     error: "Job not found".to_string(),
+                    error_id: None,
                 })),
             )
         }
         Err(e) => {
-            tracing::error!("Failed to get job status for job_id {}: {:?}", job_id, e);
+            tracing::error!("Failed to get job status for job_id {}", job_id);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(GetJobStatusResponseEnum::Error(ErrorResponse {
-                    error: "An internal error occurred. Please try again later.".to_string(),
-                })),
+                Json(GetJobStatusResponseEnum::Error(ErrorResponse::internal(&e))),
             )
         }
     }
@@ -51,7 +66,8 @@ mod tests {
 
     use crate::{handlers::fixtures::TestContext, types::GetJobStatusResponseEnum};
     use axum::{http::StatusCode, Json};
-    use db_access::models::JobStatus;
+    use db_access::models::{JobResult, JobStatus, ProvenValues};
+    use db_access::queries::{get_job_request, update_job_status};
     use serde_json::json;
 
     #[tokio::test]
@@ -108,6 +124,61 @@ mod tests {
         assert_eq!(response.status.unwrap(), JobStatus::Failed);
     }
 
+    #[tokio::test]
+    async fn test_get_job_status_failed_reports_error_and_timestamps() {
+        let ctx = TestContext::new().await;
+        let job_id = "failed_with_error_job_id";
+
+        ctx.create_job(job_id, JobStatus::Pending).await;
+        update_job_status(
+            ctx.offchain_processor_db.clone(),
+            job_id,
+            JobStatus::Failed,
+            Some(json!({ "error": "Error calling proving service" })),
+        )
+        .await
+        .unwrap();
+
+        let (status, Json(response)) = ctx.get_job_status(job_id).await;
+
+        let response = match response {
+            GetJobStatusResponseEnum::Success(success_res) => success_res,
+            GetJobStatusResponseEnum::Error(_) => panic!("Unexpected response status"),
+        };
+        let job = get_job_request(ctx.offchain_processor_db.clone(), job_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.status.unwrap(), JobStatus::Failed);
+        assert_eq!(
+            response.error.as_deref(),
+            Some("Error calling proving service")
+        );
+        assert_eq!(response.created_at, Some(job.created_at));
+        assert_eq!(response.updated_at, Some(job.updated_at));
+        assert!(job.updated_at >= job.created_at);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_status_pending_has_no_error() {
+        let ctx = TestContext::new().await;
+        let job_id = "pending_job_id";
+
+        ctx.create_job(job_id, JobStatus::Pending).await;
+
+        let (_, Json(response)) = ctx.get_job_status(job_id).await;
+
+        let response = match response {
+            GetJobStatusResponseEnum::Success(success_res) => success_res,
+            GetJobStatusResponseEnum::Error(_) => panic!("Unexpected response status"),
+        };
+        assert_eq!(response.error, None);
+        assert!(response.created_at.is_some());
+        assert_eq!(response.created_at, response.updated_at);
+    }
+
     #[tokio::test]
     async fn test_get_job_status_completed() {
         let ctx = TestContext::new().await;
@@ -134,4 +205,112 @@ mod tests {
         assert_eq!(response.job_id, job_id);
         assert_eq!(response.status.unwrap(), JobStatus::Completed);
     }
+
+    #[tokio::test]
+    async fn test_job_result_round_trip() {
+        let ctx = TestContext::new().await;
+        let job_id = "job_with_result";
+
+        ctx.create_job(job_id, JobStatus::Pending).await;
+        let result = JobResult {
+            outputs: ProvenValues {
+                twap: Some(12345.67),
+                reserve_price: Some(3456.78),
+                max_return: Some(0.25),
+            },
+            receipt_ref: Some("receipt-1".to_string()),
+            tx_hash: Some("0xabc".to_string()),
+        };
+        ctx.store_job_result(job_id, JobStatus::Completed, &result)
+            .await;
+
+        assert_eq!(ctx.get_job_result(job_id).await.unwrap(), Some(result));
+        let (_, Json(response)) = ctx.get_job_status(job_id).await;
+        match response {
+            GetJobStatusResponseEnum::Success(success_res) => {
+                assert_eq!(success_res.status.unwrap(), JobStatus::Completed)
+            }
+            GetJobStatusResponseEnum::Error(_) => panic!("Unexpected response status"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_result_missing() {
+        let ctx = TestContext::new().await;
+
+        ctx.create_job("pending_job_id", JobStatus::Pending).await;
+
+        assert_eq!(ctx.get_job_result("pending_job_id").await.unwrap(), None);
+        assert_eq!(
+            ctx.get_job_result("non_existent_job_id").await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_job_result_rejects_malformed_result() {
+        let ctx = TestContext::new().await;
+        let job_id = "malformed_result_job_id";
+
+        ctx.create_job_with_result(job_id, JobStatus::Completed, json!({ "outputs": 42 }))
+            .await;
+
+        assert!(ctx.get_job_result(job_id).await.is_err());
+    }
+
+    fn sample_job_result() -> JobResult {
+        JobResult {
+            outputs: ProvenValues {
+                twap: Some(12345.67),
+                reserve_price: Some(3456.78),
+                max_return: Some(0.25),
+            },
+            receipt_ref: Some("receipt-1".to_string()),
+            tx_hash: Some("0xabc".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compressed_job_result_round_trip() {
+        let ctx = TestContext::new().await.with_compressed_results().await;
+        let job_id = "compressed_job";
+
+        ctx.create_job(job_id, JobStatus::Pending).await;
+        let result = sample_job_result();
+        ctx.store_job_result(job_id, JobStatus::Completed, &result)
+            .await;
+
+        assert_eq!(ctx.result_encoding(job_id).await, "gzip");
+        assert_eq!(ctx.get_job_result(job_id).await.unwrap(), Some(result));
+    }
+
+    #[tokio::test]
+    async fn test_job_results_read_in_either_encoding() {
+        let ctx = TestContext::new().await;
+        let result = sample_job_result();
+
+        ctx.create_job("plain_job", JobStatus::Pending).await;
+        ctx.store_job_result("plain_job", JobStatus::Completed, &result)
+            .await;
+
+        let ctx = ctx.with_compressed_results().await;
+        ctx.create_job("compressed_job", JobStatus::Pending).await;
+        ctx.store_job_result("compressed_job", JobStatus::Completed, &result)
+            .await;
+
+        assert_eq!(ctx.result_encoding("plain_job").await, "json");
+        assert_eq!(ctx.result_encoding("compressed_job").await, "gzip");
+        for job_id in ["plain_job", "compressed_job"] {
+            assert_eq!(
+                ctx.get_job_result(job_id).await.unwrap(),
+                Some(result.clone())
+            );
+
+            let job = get_job_request(ctx.offchain_processor_db.clone(), job_id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(job.result, Some(serde_json::to_value(&result).unwrap()));
+        }
+    }
 }
@@ -0,0 +1,105 @@
+use crate::types::{ErrorResponse, JobsSummary, JobsSummaryResponseEnum};
+use crate::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use db_access::models::JobStatus;
+use db_access::queries::count_jobs_by_status;
+
+#[axum::debug_handler]
+pub async fn get_jobs_summary(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<JobsSummaryResponseEnum>) {
+    match count_jobs_by_status(state.offchain_processor_db).await {
+        Ok(counts) => {
+            let count = |status| counts.get(&status).copied().unwrap_or_default();
+            (
+                StatusCode::OK,
+                Json(JobsSummaryResponseEnum::Success(JobsSummary {
+                    pending: count(JobStatus::Pending),
+                    completed: count(JobStatus::Completed),
+                    failed: count(JobStatus::Failed),
+                })),
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to count jobs by status");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(JobsSummaryResponseEnum::Error(ErrorResponse::internal(&e))),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        handlers::fixtures::TestContext,
+        types::{JobsSummary, JobsSummaryResponseEnum},
+    };
+    use axum::{http::StatusCode, Json};
+    use db_access::{models::JobStatus, queries::count_jobs_by_status};
+
+    #[tokio::test]
+    async fn test_count_jobs_by_status() {
+        let ctx = TestContext::new().await;
+
+        ctx.create_job("pending_1", JobStatus::Pending).await;
+        ctx.create_job("pending_2", JobStatus::Pending).await;
+        ctx.create_job("completed_1", JobStatus::Completed).await;
+
+        let counts = count_jobs_by_status(ctx.offchain_processor_db.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&JobStatus::Pending], 2);
+        assert_eq!(counts[&JobStatus::Completed], 1);
+        assert!(!counts.contains_key(&JobStatus::Failed));
+    }
+
+    #[tokio::test]
+    async fn test_get_jobs_summary() {
+        let ctx = TestContext::new().await;
+
+        ctx.create_job("pending_1", JobStatus::Pending).await;
+        ctx.create_job("completed_1", JobStatus::Completed).await;
+        ctx.create_job("completed_2", JobStatus::Completed).await;
+        ctx.create_job("failed_1", JobStatus::Failed).await;
+        ctx.create_job("failed_2", JobStatus::Failed).await;
+        ctx.create_job("failed_3", JobStatus::Failed).await;
+
+        let (status, Json(response)) = ctx.get_jobs_summary().await;
+
+        let response = match response {
+            JobsSummaryResponseEnum::Success(summary) => summary,
+            JobsSummaryResponseEnum::Error(_) => panic!("Unexpected response status"),
+        };
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            response,
+            JobsSummary {
+                pending: 1,
+                completed: 2,
+                failed: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_jobs_summary_empty() {
+        let ctx = TestContext::new().await;
+
+        let (status, Json(response)) = ctx.get_jobs_summary().await;
+
+        let response = match response {
+            JobsSummaryResponseEnum::Success(summary) => summary,
+            JobsSummaryResponseEnum::Error(_) => panic!("Unexpected response status"),
+        };
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response, JobsSummary::default());
+    }
+}
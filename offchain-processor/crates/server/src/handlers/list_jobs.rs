@@ -0,0 +1,164 @@
+use crate::types::{ErrorResponse, JobList, JobListEntry, JobListResponseEnum};
+use crate::AppState;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use db_access::models::JobStatus;
+use db_access::queries::list_job_requests;
+use serde::Deserialize;
+
+pub const DEFAULT_LIST_LIMIT: i64 = 50;
+pub const MAX_LIST_LIMIT: i64 = 200;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListJobsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub status: Option<JobStatus>,
+}
+
+impl ListJobsQuery {
+    // Clamps the requested page into the allowed range
+    fn page(&self) -> (i64, i64) {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let offset = self.offset.unwrap_or_default().max(0);
+        (limit, offset)
+    }
+}
+
+#[axum::debug_handler]
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<ListJobsQuery>,
+) -> (StatusCode, Json<JobListResponseEnum>) {
+    let (limit, offset) = query.page();
+
+    match list_job_requests(state.offchain_processor_db, limit, offset, query.status).await {
+        Ok(jobs) => (
+            StatusCode::OK,
+            Json(JobListResponseEnum::Success(JobList {
+                jobs: jobs
+                    .into_iter()
+                    .map(|job| JobListEntry {
+                        job_id: job.job_id,
+                        status: job.status,
+                        created_at: job.created_at,
+                    })
+                    .collect(),
+                limit,
+                offset,
+            })),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to list jobs");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(JobListResponseEnum::Error(ErrorResponse::internal(&e))),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ListJobsQuery, DEFAULT_LIST_LIMIT, MAX_LIST_LIMIT};
+    use crate::{
+        handlers::fixtures::TestContext,
+        types::{JobList, JobListResponseEnum},
+    };
+    use axum::{http::StatusCode, Json};
+    use db_access::models::JobStatus;
+
+    fn expect_success(response: JobListResponseEnum) -> JobList {
+        match response {
+            JobListResponseEnum::Success(list) => list,
+            JobListResponseEnum::Error(_) => panic!("Unexpected response status"),
+        }
+    }
+
+    fn job_ids(list: &JobList) -> Vec<&str> {
+        list.jobs.iter().map(|job| job.job_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_page_defaults_and_bounds() {
+        assert_eq!(ListJobsQuery::default().page(), (DEFAULT_LIST_LIMIT, 0));
+
+        let query = ListJobsQuery {
+            limit: Some(1000),
+            offset: Some(-5),
+            status: None,
+        };
+        assert_eq!(query.page(), (MAX_LIST_LIMIT, 0));
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_empty() {
+        let ctx = TestContext::new().await;
+
+        let (status, Json(response)) = ctx.list_jobs(ListJobsQuery::default()).await;
+        let list = expect_success(response);
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(list.jobs.is_empty());
+        assert_eq!(list.limit, DEFAULT_LIST_LIMIT);
+        assert_eq!(list.offset, 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_pages_newest_first() {
+        let ctx = TestContext::new().await;
+
+        ctx.create_job("job_1", JobStatus::Pending).await;
+        ctx.create_job("job_2", JobStatus::Completed).await;
+        ctx.create_job("job_3", JobStatus::Failed).await;
+
+        let (status, Json(response)) = ctx
+            .list_jobs(ListJobsQuery {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await;
+        let first_page = expect_success(response);
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(job_ids(&first_page), ["job_3", "job_2"]);
+        assert_eq!(first_page.jobs[0].status, JobStatus::Failed);
+
+        let (_, Json(response)) = ctx
+            .list_jobs(ListJobsQuery {
+                limit: Some(2),
+                offset: Some(2),
+                ..Default::default()
+            })
+            .await;
+        let second_page = expect_success(response);
+
+        assert_eq!(job_ids(&second_page), ["job_1"]);
+        assert_eq!(second_page.offset, 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_filters_by_status() {
+        let ctx = TestContext::new().await;
+
+        ctx.create_job("pending_1", JobStatus::Pending).await;
+        ctx.create_job("completed_1", JobStatus::Completed).await;
+        ctx.create_job("pending_2", JobStatus::Pending).await;
+
+        let (status, Json(response)) = ctx
+            .list_jobs(ListJobsQuery {
+                status: Some(JobStatus::Pending),
+                ..Default::default()
+            })
+            .await;
+        let list = expect_success(response);
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(job_ids(&list), ["pending_2", "pending_1"]);
+        assert!(list.jobs.iter().all(|job| job.status == JobStatus::Pending));
+    }
+}
@@ -1,6 +1,12 @@
 pub mod api_key;
+pub mod errors;
 #[cfg(test)]
 pub mod fixtures;
 pub mod get_pricing_data;
 pub mod health_check;
+pub mod job_events;
+pub mod job_result;
 pub mod job_status;
+pub mod jobs_summary;
+pub mod list_jobs;
+pub mod version;
@@ -0,0 +1,33 @@
+use axum::Json;
+
+use crate::types::VersionInfo;
+
+// Reports which build is deployed. The offchain processor runs no guest, so unlike the
+// proving service there is no guest image id to report.
+pub async fn get_version() -> Json<VersionInfo> {
+    Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("GIT_COMMIT").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get, Router};
+    use axum_test::TestServer;
+
+    #[tokio::test]
+    async fn test_get_version() {
+        let app = Router::new().route("/version", get(get_version));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/version").await;
+
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(body["git_commit"], env!("GIT_COMMIT"));
+        assert!(body.get("guest_image_id").is_none());
+    }
+}
@@ -43,6 +43,7 @@ pub async fn simple_apikey_auth(
 
                     let response_data = ErrorResponse {
                         error: format!("Authentication failed: {}", error_detail),
+                        error_id: None,
                     };
 
                     Ok((StatusCode::UNAUTHORIZED, Json(response_data)).into_response())
@@ -53,6 +54,7 @@ pub async fn simple_apikey_auth(
 
             let response_data = ErrorResponse {
                 error: "Authentication failed: Invalid API key format".to_string(),
+                error_id: None,
             };
 
             Ok((StatusCode::UNAUTHORIZED, Json(response_data)).into_response())
@@ -63,6 +65,7 @@ pub async fn simple_apikey_auth(
 
         let response_data = ErrorResponse {
             error: "Authentication failed: No API key provided in headers".to_string(),
+            error_id: None,
         };
 
         Ok((StatusCode::UNAUTHORIZED, Json(response_data)).into_response())
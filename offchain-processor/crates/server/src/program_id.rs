@@ -0,0 +1,70 @@
+use eyre::{eyre, Result};
+use starknet_types_core::felt::Felt;
+
+/// Longest string that fits in a single felt as a Cairo short string.
+pub const MAX_SHORT_STRING_LEN: usize = 31;
+
+/// Encodes a program id the way Cairo encodes a short string literal such as `'PITCHLAKE_V1'`:
+/// its ASCII bytes, read as a big-endian number.
+pub fn program_id_to_felt(id: &str) -> Result<Felt> {
+    if !id.is_ascii() {
+        return Err(eyre!("Program id {} is not ASCII", id));
+    }
+    if id.len() > MAX_SHORT_STRING_LEN {
+        return Err(eyre!(
+            "Program id {} is longer than {} characters",
+            id,
+            MAX_SHORT_STRING_LEN
+        ));
+    }
+
+    Ok(Felt::from_bytes_be_slice(id.as_bytes()))
+}
+
+/// Parses a configured program id, given either as a `0x`-prefixed felt, as the verifier
+/// stores it, or as the short string it encodes.
+pub fn parse_program_id(value: &str) -> Result<Felt> {
+    match value.strip_prefix("0x") {
+        Some(_) => Felt::from_hex(value).map_err(|e| eyre!("Invalid program id {}: {}", value, e)),
+        None => program_id_to_felt(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_id_to_felt_uses_short_string_encoding() {
+        // 'PITCHLAKE_V1' in Cairo
+        assert_eq!(
+            program_id_to_felt("PITCHLAKE_V1").unwrap(),
+            Felt::from_hex("0x50495443484c414b455f5631").unwrap()
+        );
+        assert_eq!(program_id_to_felt("A").unwrap(), Felt::from(0x41));
+        assert_eq!(program_id_to_felt("").unwrap(), Felt::ZERO);
+    }
+
+    #[test]
+    fn test_program_id_to_felt_rejects_invalid_short_strings() {
+        assert!(program_id_to_felt(&"a".repeat(MAX_SHORT_STRING_LEN)).is_ok());
+
+        let err = program_id_to_felt(&"a".repeat(MAX_SHORT_STRING_LEN + 1)).unwrap_err();
+        assert!(err.to_string().contains("longer than 31 characters"));
+
+        let err = program_id_to_felt("pitchlaké").unwrap_err();
+        assert_eq!(err.to_string(), "Program id pitchlaké is not ASCII");
+    }
+
+    #[test]
+    fn test_parse_program_id_accepts_felts_and_short_strings() {
+        let expected = program_id_to_felt("PITCHLAKE_V1").unwrap();
+
+        assert_eq!(parse_program_id("PITCHLAKE_V1").unwrap(), expected);
+        assert_eq!(
+            parse_program_id("0x50495443484c414b455f5631").unwrap(),
+            expected
+        );
+        assert!(parse_program_id("0xnot_hex").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use db_access::models::JobStatus;
+use db_access::models::{JobResult, JobStatus};
 use serde::{Deserialize, Serialize};
 
 // timestamp ranges for each sub-job calculation
@@ -14,6 +14,12 @@ pub struct PitchLakeJobRequest {
     pub identifiers: Vec<String>,
     pub params: PitchLakeJobRequestParams,
     pub client_info: ClientInfo, // New field
+    // Opaque client value, passed through to the proving service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    // Routes the request to the canary proving service, when one is configured
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub canary: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -23,11 +29,80 @@ pub struct ClientInfo {
     pub timestamp: i64,
 }
 
+// Time range of a single proof, as expected by the proving service
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProvingTimeRange {
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+impl From<(i64, i64)> for ProvingTimeRange {
+    fn from((start_timestamp, end_timestamp): (i64, i64)) -> Self {
+        Self {
+            start_timestamp,
+            end_timestamp,
+        }
+    }
+}
+
+// Job request payload sent to the proving service
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProvingJobRequest {
+    pub job_group_id: String,
+    pub twap: ProvingTimeRange,
+    pub reserve_price: ProvingTimeRange,
+    pub max_return: ProvingTimeRange,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+// Status the proving service answers a job request with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvingServiceStatus {
+    Success,
+    Error,
+    // Proof generation is turned off, the job will never complete
+    Disabled,
+}
+
+// Response of the proving service to a job request
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProvingServiceResponse {
+    pub status: ProvingServiceStatus,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(flatten)]
+    pub result: JobResult,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JobResponse {
     pub job_id: String,
     pub message: Option<String>,
     pub status: Option<JobStatus>,
+    // Set on internal errors, to correlate the response with the server logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_id: Option<String>,
+    // Every field of an invalid request that failed validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+// A request field that failed validation, named by its path in the request body
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
 }
 
 impl JobResponse {
@@ -36,6 +111,8 @@ impl JobResponse {
             job_id,
             message,
             status,
+            error_id: None,
+            errors: Vec::new(),
         }
     }
 }
@@ -43,11 +120,82 @@ impl JobResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_id: Option<String>,
+}
+
+// Status of a job, with when it was created and last changed. The timestamps and error are
+// optional so clients of the plain `JobResponse` shape keep working.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JobStatusResponse {
+    pub job_id: String,
+    pub message: Option<String>,
+    pub status: Option<JobStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::NaiveDateTime>,
+    // Why the job failed, only set on failed jobs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum GetJobStatusResponseEnum {
-    Success(JobResponse),
+    Success(JobStatusResponse),
+    Error(ErrorResponse),
+}
+
+// Any JSON deserializes as a stored result, so it is tried last
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum GetJobResultResponseEnum {
+    Pending(JobResponse),
+    Error(ErrorResponse),
+    Success(serde_json::Value),
+}
+
+// Number of jobs in each status
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobsSummary {
+    pub pending: i64,
+    pub completed: i64,
+    pub failed: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum JobsSummaryResponseEnum {
+    Success(JobsSummary),
     Error(ErrorResponse),
 }
+
+// A job as listed by `GET /jobs`
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobListEntry {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobList {
+    pub jobs: Vec<JobListEntry>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum JobListResponseEnum {
+    Success(JobList),
+    Error(ErrorResponse),
+}
+
+// Build info reported by `GET /version`
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_commit: String,
+}
@@ -0,0 +1,7 @@
+-- Receipts of generated proofs, keyed by the job they were generated for
+CREATE TABLE IF NOT EXISTS public.proof_receipts (
+    job_id TEXT NOT NULL,
+    receipt BYTEA NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT proof_receipts_pkey PRIMARY KEY (job_id)
+);
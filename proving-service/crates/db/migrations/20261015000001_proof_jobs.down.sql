@@ -0,0 +1,2 @@
+-- Drop the proof_jobs table if it exists
+DROP TABLE IF EXISTS public.proof_jobs;
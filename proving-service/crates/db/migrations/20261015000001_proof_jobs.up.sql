@@ -0,0 +1,8 @@
+-- Jobs dispatched to the proof handlers, keyed by their processing key, finished once the
+-- handlers are done with them
+CREATE TABLE IF NOT EXISTS public.proof_jobs (
+    job_key TEXT NOT NULL,
+    dispatched_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP WITHOUT TIME ZONE,
+    CONSTRAINT proof_jobs_pkey PRIMARY KEY (job_key)
+);
@@ -0,0 +1,2 @@
+-- Drop the ranges column if it exists
+ALTER TABLE public.proof_jobs DROP COLUMN IF EXISTS ranges;
@@ -0,0 +1,2 @@
+-- Time ranges each job is proven over, as JSON, recorded when a handler starts on the job
+ALTER TABLE public.proof_jobs ADD COLUMN IF NOT EXISTS ranges JSONB;
@@ -59,6 +59,10 @@ pub async fn get_block_headers_by_time_range(
     Ok(headers)
 }
 
+/// Returns the base fees of the blocks in the time range, in block order.
+///
+/// Blocks without a base fee (e.g. pre-London blocks) are skipped rather than failing the whole
+/// fetch, so the result may hold fewer entries than there are blocks in the range.
 pub async fn get_block_base_fee_by_time_range(
     db: Arc<DbConnection>,
     start_timestamp: i64,
@@ -75,6 +79,7 @@ pub async fn get_block_base_fee_by_time_range(
         SELECT base_fee_per_gas
         FROM blockheaders
         WHERE CAST(timestamp AS BIGINT) BETWEEN $1 AND $2
+            AND base_fee_per_gas IS NOT NULL
         ORDER BY number ASC
         "#,
     )
@@ -230,6 +235,30 @@ mod tests {
         assert_eq!(base_fees[2], "0xa85f1d");
     }
 
+    #[tokio::test]
+    async fn test_should_skip_null_block_base_fee() {
+        let test_db = setup_db().await;
+
+        sqlx::query(
+            r#"
+            INSERT INTO blockheaders
+            (block_hash, number, gas_limit, gas_used, nonce, transaction_root, base_fee_per_gas, receipts_root, state_root, timestamp)
+            VALUES
+            ('0x6', 8006486, 100, 50, '0xnonce6', '0xtx6', NULL, '0xreceipt6', '0xstate6', 1743249130)
+            "#,
+        )
+        .execute(&test_db.db.pool)
+        .await
+        .expect("Failed to insert block without base fee");
+
+        let base_fees = get_block_base_fee_by_time_range(test_db.db, 1743249000, 1743249130)
+            .await
+            .unwrap();
+
+        assert_eq!(base_fees.len(), 5);
+        assert_eq!(base_fees[4], "0x9fda11");
+    }
+
     #[tokio::test]
     async fn test_should_get_block_base_fee_by_time_range_with_no_results() {
         let test_db = setup_db().await;
@@ -0,0 +1,29 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use message_handler::proof_composition::HASHING_INPUT_LEN;
+use message_handler::proof_composition::hashing_input::{
+    build_hashing_felts, build_hashing_felts_naive,
+};
+
+fn raw_input(len: usize) -> Vec<String> {
+    (0..len)
+        .map(|i| format!("0x{:x}", 30_000_000_000u64 + i as u64 * 7_919))
+        .collect()
+}
+
+fn bench_build_hashing_felts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_hashing_felts");
+    // A single hour repeated, a week of hourly fees, and a full span
+    for raw_len in [1, 168, HASHING_INPUT_LEN] {
+        let raw = raw_input(raw_len);
+        group.bench_with_input(BenchmarkId::new("naive", raw_len), &raw, |b, raw| {
+            b.iter(|| build_hashing_felts_naive(black_box(raw), HASHING_INPUT_LEN))
+        });
+        group.bench_with_input(BenchmarkId::new("optimized", raw_len), &raw, |b, raw| {
+            b.iter(|| build_hashing_felts(black_box(raw), HASHING_INPUT_LEN))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_build_hashing_felts);
+criterion_main!(benches);
@@ -0,0 +1,160 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+
+fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+// Unset and empty variables are treated the same, so `FOO=` in a `.env` file falls back to the
+// default
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+fn parse_value<T>(name: &str, value: &str, expected: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| eyre!("{} must be {}, got {:?}: {}", name, expected, value, e))
+}
+
+fn required_value(name: &str, value: Option<&str>) -> Result<String> {
+    non_empty(value)
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("{} environment variable not set", name))
+}
+
+fn bool_value(name: &str, value: Option<&str>, default: bool) -> Result<bool> {
+    match non_empty(value).map(str::to_lowercase).as_deref() {
+        None => Ok(default),
+        Some("true" | "1") => Ok(true),
+        Some("false" | "0") => Ok(false),
+        Some(other) => Err(eyre!(
+            "{} must be a boolean (true, false, 1 or 0), got {:?}",
+            name,
+            other
+        )),
+    }
+}
+
+fn u64_value(name: &str, value: Option<&str>, default: u64) -> Result<u64> {
+    non_empty(value).map_or(Ok(default), |value| {
+        parse_value(name, value, "a non-negative integer")
+    })
+}
+
+fn duration_secs_value(name: &str, value: Option<&str>, default: Duration) -> Result<Duration> {
+    non_empty(value).map_or(Ok(default), |value| {
+        parse_value(name, value, "a number of seconds").map(Duration::from_secs)
+    })
+}
+
+/// Returns the variable's value, failing if it is unset or empty.
+pub fn required_env(name: &str) -> Result<String> {
+    required_value(name, var(name).as_deref())
+}
+
+/// Returns the variable's value, or `None` if it is unset or empty.
+pub fn optional_env(name: &str) -> Option<String> {
+    non_empty(var(name).as_deref()).map(str::to_string)
+}
+
+/// Parses the variable with [`FromStr`], or returns `None` if it is unset or empty.
+pub fn parse_env<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    optional_env(name)
+        .map(|value| parse_value(name, &value, "a valid value"))
+        .transpose()
+}
+
+/// Parses `true`/`false`/`1`/`0` (case-insensitive), or returns `default` if unset.
+pub fn bool_env(name: &str, default: bool) -> Result<bool> {
+    bool_value(name, var(name).as_deref(), default)
+}
+
+/// Parses a non-negative integer, or returns `default` if unset.
+pub fn u64_env(name: &str, default: u64) -> Result<u64> {
+    u64_value(name, var(name).as_deref(), default)
+}
+
+/// Parses a whole number of seconds, or returns `default` if unset.
+pub fn duration_secs_env(name: &str, default: Duration) -> Result<Duration> {
+    duration_secs_value(name, var(name).as_deref(), default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_value() {
+        assert_eq!(required_value("NAME", Some("value")).unwrap(), "value");
+        assert_eq!(
+            required_value("NAME", None).unwrap_err().to_string(),
+            "NAME environment variable not set"
+        );
+        assert!(required_value("NAME", Some(" ")).is_err());
+    }
+
+    #[test]
+    fn test_required_env_unset() {
+        assert!(required_env("FOSSIL_TEST_UNSET_REQUIRED_ENV").is_err());
+    }
+
+    #[test]
+    fn test_bool_value() {
+        assert!(bool_value("FLAG", None, true).unwrap());
+        assert!(!bool_value("FLAG", Some(""), false).unwrap());
+        assert!(bool_value("FLAG", Some("TRUE"), false).unwrap());
+        assert!(bool_value("FLAG", Some("1"), false).unwrap());
+        assert!(!bool_value("FLAG", Some("false"), true).unwrap());
+        assert!(!bool_value("FLAG", Some("0"), true).unwrap());
+        assert_eq!(
+            bool_value("FLAG", Some("yes"), false)
+                .unwrap_err()
+                .to_string(),
+            "FLAG must be a boolean (true, false, 1 or 0), got \"yes\""
+        );
+    }
+
+    #[test]
+    fn test_u64_value() {
+        assert_eq!(u64_value("COUNT", None, 64).unwrap(), 64);
+        assert_eq!(u64_value("COUNT", Some(" 12 "), 64).unwrap(), 12);
+        assert!(u64_value("COUNT", Some("-1"), 64).is_err());
+        assert!(
+            u64_value("COUNT", Some("many"), 64)
+                .unwrap_err()
+                .to_string()
+                .starts_with("COUNT must be a non-negative integer, got \"many\"")
+        );
+    }
+
+    #[test]
+    fn test_duration_secs_value() {
+        let default = Duration::from_secs(300);
+        assert_eq!(duration_secs_value("SECS", None, default).unwrap(), default);
+        assert_eq!(
+            duration_secs_value("SECS", Some("30"), default).unwrap(),
+            Duration::from_secs(30)
+        );
+        assert!(duration_secs_value("SECS", Some("1.5"), default).is_err());
+        assert!(duration_secs_value("SECS", Some("30s"), default).is_err());
+    }
+
+    #[test]
+    fn test_parse_env_unset() {
+        assert_eq!(
+            parse_env::<u64>("FOSSIL_TEST_UNSET_PARSE_ENV").unwrap(),
+            None
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use aws_config::BehaviorVersion;
 use aws_config::load_defaults;
 use eyre::Result;
+use message_handler::env_util::required_env;
 use message_handler::queue::sqs_message_queue::SqsMessageQueue;
 use message_handler::services::example_message_handler::ExampleMessageHandler;
 use message_handler::services::job_dispatcher::JobDispatcher;
@@ -15,8 +16,7 @@ async fn main() -> Result<()> {
     dotenv::dotenv().ok();
 
     // This example tests with real sqs, but you can replace this with a local queue.
-    let queue_url = std::env::var("SQS_QUEUE_URL")
-        .map_err(|e| eyre::eyre!("SQS_QUEUE_URL environment variable not set: {}", e))?;
+    let queue_url = required_env("SQS_QUEUE_URL")?;
 
     // Configure tracing
     tracing_subscriber::fmt().init();
@@ -60,6 +60,13 @@ async fn main() -> Result<()> {
                             0 // Fallback value
                         }
                     },
+                    priority: None,
+                    tag: None,
+                    deadline_ts: None,
+                    settlement_timestamp: None,
+                    timeout_secs: None,
+                    at_block_hash: None,
+                    ranges: None,
                 }))
                 .await;
             println!("Job dispatched: {:?}", result);
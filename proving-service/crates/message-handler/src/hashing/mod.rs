@@ -1,13 +1,31 @@
+use std::{fmt, future::Future, str::FromStr, time::Duration};
+
 use async_trait::async_trait;
 #[cfg(feature = "proof-composition")]
 use coprocessor_common::convert_felt_to_f64;
+use eyre::eyre;
+use risc0_zkvm::sha::{Impl, Sha256};
 use starknet::{
-    accounts::{Account, SingleOwnerAccount},
-    core::types::{BlockId, BlockTag, Call, Felt, FunctionCall, InvokeTransactionResult, U256},
+    accounts::{Account, ExecutionEncoding, SingleOwnerAccount},
+    core::{
+        chain_id,
+        types::{
+            BlockId, BlockTag, Call, Felt, FunctionCall, InvokeTransactionResult, StarknetError,
+            TransactionExecutionStatus, U256,
+        },
+    },
     macros::selector,
-    providers::{JsonRpcClient, Provider, ProviderError, jsonrpc::HttpTransport},
-    signers::LocalWallet,
+    providers::{
+        JsonRpcClient, Provider, ProviderError, Url,
+        jsonrpc::{HttpTransport, HttpTransportError, JsonRpcClientError},
+    },
+    signers::{LocalWallet, SigningKey},
 };
+use tracing::warn;
+
+use crate::env_util::{optional_env, parse_env, required_env};
+use crate::services::hashing_service::RetryBudget;
+use crate::services::jobs::RequestProof;
 
 #[cfg(not(feature = "proof-composition"))]
 pub fn convert_felt_to_f64(felt: Felt) -> f64 {
@@ -18,11 +36,243 @@ pub fn convert_felt_to_f64(felt: Felt) -> f64 {
     high_bits + low_bits
 }
 
+/// Bound up to which an f64 represents every integer exactly.
+pub const MAX_EXACT_F64_INT: u64 = 1 << 53;
+
+/// A felt too large to be converted to an f64 without losing precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeltConversionError(pub Felt);
+
+impl fmt::Display for FeltConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Felt {:#x} exceeds 2^53 and can't be represented exactly as an f64",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for FeltConversionError {}
+
+/// Converts a felt to an f64 like [`convert_felt_to_f64`], failing instead of losing precision
+/// when it exceeds 2^53.
+pub fn try_convert_felt_to_f64(felt: Felt) -> Result<f64, FeltConversionError> {
+    if felt > Felt::from(MAX_EXACT_F64_INT) {
+        return Err(FeltConversionError(felt));
+    }
+    Ok(convert_felt_to_f64(felt))
+}
+
+/// Converts the average fees of a range, failing on the whole range if any fee can't be
+/// converted exactly: dropping it would shift every later fee to the wrong hour.
+pub fn try_convert_avg_fees(felts: &[Felt]) -> Result<Vec<f64>, ProviderError> {
+    felts
+        .iter()
+        .map(|fee| {
+            try_convert_felt_to_f64(*fee).map_err(|e| {
+                ProviderError::StarknetError(StarknetError::UnexpectedError(e.to_string()))
+            })
+        })
+        .collect()
+}
+
+/// A hash stored by the hash storage contract, as its eight 32-bit limbs. The contract returns
+/// all zeros for a hash it hasn't stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StoredHash(pub [u32; 8]);
+
+impl StoredHash {
+    /// Whether the contract has no hash stored.
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 8]
+    }
+
+    /// Reads the hash from the felts of a contract call result, one limb per felt.
+    pub fn from_felts(felts: &[Felt]) -> Result<Self, ProviderError> {
+        if felts.len() != 8 {
+            return Err(ProviderError::ArrayLengthMismatch);
+        }
+
+        let mut limbs = [0; 8];
+        for (limb, felt) in limbs.iter_mut().zip(felts) {
+            *limb = U256::from(*felt).low() as u32;
+        }
+
+        Ok(Self(limbs))
+    }
+
+    /// The hash `hash_avg_fees_and_store` stores for `avg_fees`: the contract's SHA-256 over
+    /// every fee as a big-endian 64-bit integer, split into its high and low 32-bit words.
+    pub fn of_avg_fees(avg_fees: &[f64]) -> Self {
+        let bytes: Vec<u8> = avg_fees
+            .iter()
+            .flat_map(|fee| (*fee as u64).to_be_bytes())
+            .collect();
+        let digest = Impl::hash_bytes(&bytes);
+
+        let mut limbs = [0; 8];
+        for (limb, word) in limbs.iter_mut().zip(digest.as_bytes().chunks_exact(4)) {
+            *limb = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        Self(limbs)
+    }
+}
+
+impl fmt::Display for StoredHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for limb in self.0 {
+            write!(f, "{:08x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+/// How the light client lays out the fees returned by `get_avg_fees_in_range`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeResponseLayout {
+    /// An array, serialized with its length first.
+    #[default]
+    LengthPrefixed,
+    /// The fees alone.
+    Raw,
+}
+
+impl FeeResponseLayout {
+    /// Extracts the fee felts from a call result laid out this way.
+    pub fn decode(self, mut felts: Vec<Felt>) -> Result<Vec<Felt>, ProviderError> {
+        match self {
+            Self::LengthPrefixed => {
+                if felts.is_empty() {
+                    return Err(ProviderError::ArrayLengthMismatch);
+                }
+                felts.remove(0);
+                Ok(felts)
+            }
+            Self::Raw => Ok(felts),
+        }
+    }
+}
+
+impl FromStr for FeeResponseLayout {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "length_prefixed" | "lengthprefixed" => Ok(Self::LengthPrefixed),
+            "raw" => Ok(Self::Raw),
+            other => Err(eyre!("Unknown fee response layout: {}", other)),
+        }
+    }
+}
+
+/// How many times a transient RPC failure is retried before giving up.
+pub const MAX_RETRIES: u32 = 3;
+
+/// Wait before the first retry, doubled on each one after.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
 pub struct HashingProvider {
     provider: JsonRpcClient<HttpTransport>,
     fossil_light_client_address: Felt,
     hash_storage_address: Felt,
     account: SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
+    block_id: BlockId,
+    /// Retries of a transient failure when reading the average fees.
+    pub max_retries: u32,
+    fee_response_layout: FeeResponseLayout,
+}
+
+/// Whether an RPC error may go away on its own: the node rate limiting us, timing out or being
+/// unreachable. Errors from the chain itself, such as a reverted call, are deterministic.
+pub fn is_transient(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::RateLimited => true,
+        ProviderError::Other(e) => matches!(
+            e.as_any().downcast_ref::<JsonRpcClientError<HttpTransportError>>(),
+            Some(JsonRpcClientError::TransportError(HttpTransportError::Reqwest(e)))
+                if e.is_timeout() || e.is_connect()
+        ),
+        ProviderError::StarknetError(_) | ProviderError::ArrayLengthMismatch => false,
+    }
+}
+
+/// Runs `call`, retrying it up to `max_retries` times with exponential backoff while it fails
+/// with a transient error. Every retry is taken from `retry_budget`, failing once it is spent.
+async fn retry_transient<T, F, Fut>(
+    max_retries: u32,
+    retry_budget: &RetryBudget,
+    mut call: F,
+) -> Result<T, ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ProviderError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < max_retries && is_transient(&e) => {
+                if !retry_budget.try_spend() {
+                    warn!(error = ?e, "Retry budget spent, giving up on RPC call");
+                    return Err(e);
+                }
+                let backoff = INITIAL_BACKOFF * 2u32.pow(attempt);
+                warn!(
+                    error = ?e,
+                    retry_in = ?backoff,
+                    "RPC call failed, retrying..."
+                );
+
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Parses a `0x`-prefixed block hash into a [`BlockId`] pinning calls to that block.
+pub fn parse_block_hash(block_hash: &str) -> Result<BlockId, String> {
+    let digits = block_hash
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("Block hash {} is not 0x-prefixed", block_hash))?;
+    if digits.is_empty() || digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Block hash {} is not a valid hex hash", block_hash));
+    }
+
+    Felt::from_hex(block_hash)
+        .map(BlockId::Hash)
+        .map_err(|e| format!("Block hash {} is not a valid felt: {}", block_hash, e))
+}
+
+/// Parses a Starknet chain id: `SEPOLIA`, `MAINNET` (case-insensitive) or a `0x`-prefixed felt.
+pub fn parse_chain_id(value: &str) -> Result<Felt, String> {
+    match value.trim().to_uppercase().as_str() {
+        "SEPOLIA" => Ok(chain_id::SEPOLIA),
+        "MAINNET" => Ok(chain_id::MAINNET),
+        _ if value.trim().starts_with("0x") => Felt::from_hex(value.trim())
+            .map_err(|e| format!("Chain id {} is not a valid felt: {}", value, e)),
+        _ => Err(format!(
+            "Chain id {} must be SEPOLIA, MAINNET or a 0x-prefixed felt",
+            value
+        )),
+    }
+}
+
+/// Reads the chain id from `STARKNET_CHAIN_ID`, defaulting to Sepolia when unset.
+pub fn chain_id_from_env() -> eyre::Result<Felt> {
+    match optional_env("STARKNET_CHAIN_ID") {
+        Some(value) => parse_chain_id(&value).map_err(|e| eyre!(e)),
+        None => Ok(chain_id::SEPOLIA),
+    }
+}
+
+fn hex_env(name: &str) -> eyre::Result<Felt> {
+    let value = required_env(name)?;
+    Felt::from_hex(&value).map_err(|e| eyre!("{} must be a hex felt, got {:?}: {}", name, value, e))
 }
 
 #[async_trait]
@@ -30,16 +280,19 @@ pub trait HashingProviderTrait {
     fn get_provider(&self) -> &JsonRpcClient<HttpTransport>;
     fn get_fossil_light_client_address(&self) -> &Felt;
     fn get_hash_storage_address(&self) -> &Felt;
+    /// Reads the average fees from `start_timestamp` to `end_timestamp`, taking retries of
+    /// transient failures from `retry_budget`.
     async fn get_avg_fees_in_range(
         &self,
         start_timestamp: u64,
         end_timestamp: u64,
+        retry_budget: &RetryBudget,
     ) -> Result<Vec<f64>, ProviderError>;
-    async fn get_hash_stored_avg_fees(&self, timestamp: u64) -> Result<[u32; 8], ProviderError>;
+    async fn get_hash_stored_avg_fees(&self, timestamp: u64) -> Result<StoredHash, ProviderError>;
     async fn get_hash_batched_avg_fees(
         &self,
         start_timestamp: u64,
-    ) -> Result<[u32; 8], ProviderError>;
+    ) -> Result<StoredHash, ProviderError>;
     async fn hash_avg_fees_and_store(
         &self,
         start_timestamp: u64,
@@ -48,6 +301,10 @@ pub trait HashingProviderTrait {
         &self,
         start_timestamp: u64,
     ) -> Result<InvokeTransactionResult, String>;
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionExecutionStatus, ProviderError>;
 }
 
 impl HashingProvider {
@@ -62,8 +319,101 @@ impl HashingProvider {
             fossil_light_client_address,
             hash_storage_address,
             account,
+            block_id: BlockId::Tag(BlockTag::Latest),
+            max_retries: MAX_RETRIES,
+            fee_response_layout: FeeResponseLayout::LengthPrefixed,
         }
     }
+
+    /// Connects to the node at `rpc_url`, signing transactions with the given account for
+    /// `chain_id`.
+    pub fn connect(
+        rpc_url: &str,
+        fossil_light_client_address: Felt,
+        hash_storage_address: Felt,
+        private_key: Felt,
+        account_address: Felt,
+        chain_id: Felt,
+    ) -> eyre::Result<Self> {
+        let rpc_url =
+            Url::parse(rpc_url).map_err(|e| eyre!("Invalid RPC URL {}: {}", rpc_url, e))?;
+        let provider = JsonRpcClient::new(HttpTransport::new(rpc_url));
+        let account = SingleOwnerAccount::new(
+            provider.clone(),
+            LocalWallet::from(SigningKey::from_secret_scalar(private_key)),
+            account_address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+
+        Ok(Self::new(
+            provider,
+            fossil_light_client_address,
+            hash_storage_address,
+            account,
+        ))
+    }
+
+    /// Connects using `RPC_URL`, `FOSSIL_LIGHT_CLIENT_ADDRESS`, `HASH_STORAGE_ADDRESS`,
+    /// `STARKNET_PRIVATE_KEY` and `STARKNET_ACCOUNT`. The chain id is read from
+    /// `STARKNET_CHAIN_ID`, defaulting to Sepolia, and the fee response layout from
+    /// `FEE_RESPONSE_LAYOUT`, defaulting to length prefixed.
+    pub fn from_env() -> eyre::Result<Self> {
+        let chain_id = chain_id_from_env()?;
+        let fee_response_layout =
+            parse_env::<FeeResponseLayout>("FEE_RESPONSE_LAYOUT")?.unwrap_or_default();
+
+        Ok(Self::connect(
+            &required_env("RPC_URL")?,
+            hex_env("FOSSIL_LIGHT_CLIENT_ADDRESS")?,
+            hex_env("HASH_STORAGE_ADDRESS")?,
+            hex_env("STARKNET_PRIVATE_KEY")?,
+            hex_env("STARKNET_ACCOUNT")?,
+            chain_id,
+        )?
+        .with_fee_response_layout(fee_response_layout))
+    }
+
+    /// Chain id the account signs transactions for.
+    pub fn chain_id(&self) -> Felt {
+        self.account.chain_id()
+    }
+
+    /// Pins all reads to the block with the given hash instead of the latest block, so results
+    /// are reproducible.
+    pub fn with_block_hash(mut self, block_hash: &str) -> Result<Self, String> {
+        self.block_id = parse_block_hash(block_hash)?;
+        Ok(self)
+    }
+
+    /// Makes reads for `request` against the block it pins with `at_block_hash`, or against the
+    /// latest block when it pins none.
+    pub fn for_request(mut self, request: &RequestProof) -> Result<Self, String> {
+        self.block_id = request.block_id()?;
+        Ok(self)
+    }
+
+    /// Retries a transient failure reading the average fees up to `max_retries` times, as long
+    /// as the caller's retry budget lasts.
+    pub const fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Decodes the average fees as laid out by the light client contract version in use.
+    pub const fn with_fee_response_layout(mut self, layout: FeeResponseLayout) -> Self {
+        self.fee_response_layout = layout;
+        self
+    }
+
+    pub const fn fee_response_layout(&self) -> FeeResponseLayout {
+        self.fee_response_layout
+    }
+
+    /// Block the contract reads are made against.
+    pub const fn block_id(&self) -> BlockId {
+        self.block_id
+    }
 }
 
 #[async_trait]
@@ -84,29 +434,25 @@ impl HashingProviderTrait for HashingProvider {
         &self,
         start_timestamp: u64,
         end_timestamp: u64,
+        retry_budget: &RetryBudget,
     ) -> Result<Vec<f64>, ProviderError> {
-        let mut call_result = self
-            .provider
-            .call(
+        let call_result = retry_transient(self.max_retries, retry_budget, || {
+            self.provider.call(
                 FunctionCall {
                     contract_address: self.fossil_light_client_address,
                     entry_point_selector: selector!("get_avg_fees_in_range"),
                     calldata: vec![Felt::from(start_timestamp), Felt::from(end_timestamp)],
                 },
-                BlockId::Tag(BlockTag::Latest),
+                self.block_id,
             )
-            .await?;
-        call_result.remove(0); // the first element is the length of the array, which is not needed by us
+        })
+        .await?;
+        let call_result = self.fee_response_layout.decode(call_result)?;
 
-        let avg_hourly_fees = call_result
-            .iter()
-            .map(|fee| convert_felt_to_f64(*fee))
-            .collect();
-
-        Ok(avg_hourly_fees)
+        try_convert_avg_fees(&call_result)
     }
 
-    async fn get_hash_stored_avg_fees(&self, timestamp: u64) -> Result<[u32; 8], ProviderError> {
+    async fn get_hash_stored_avg_fees(&self, timestamp: u64) -> Result<StoredHash, ProviderError> {
         let call_result = self
             .provider
             .call(
@@ -115,22 +461,17 @@ impl HashingProviderTrait for HashingProvider {
                     entry_point_selector: selector!("get_hash_stored_avg_fees"),
                     calldata: vec![Felt::from(timestamp)],
                 },
-                BlockId::Tag(BlockTag::Latest),
+                self.block_id,
             )
             .await?;
 
-        let mut result = [0; 8];
-        for i in 0..8 {
-            result[i] = U256::from(call_result[i]).low() as u32;
-        }
-
-        Ok(result)
+        StoredHash::from_felts(&call_result)
     }
 
     async fn get_hash_batched_avg_fees(
         &self,
         start_timestamp: u64,
-    ) -> Result<[u32; 8], ProviderError> {
+    ) -> Result<StoredHash, ProviderError> {
         let call_result = self
             .provider
             .call(
@@ -139,16 +480,11 @@ impl HashingProviderTrait for HashingProvider {
                     entry_point_selector: selector!("get_hash_stored_batched_avg_fees"),
                     calldata: vec![Felt::from(start_timestamp)],
                 },
-                BlockId::Tag(BlockTag::Latest),
+                self.block_id,
             )
             .await?;
 
-        let mut result = [0; 8];
-        for i in 0..8 {
-            result[i] = U256::from(call_result[i]).low() as u32;
-        }
-
-        Ok(result)
+        StoredHash::from_felts(&call_result)
     }
 
     async fn hash_avg_fees_and_store(
@@ -180,17 +516,27 @@ impl HashingProviderTrait for HashingProvider {
             .await
             .map_err(|_| "Error".to_string())
     }
+
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionExecutionStatus, ProviderError> {
+        let receipt = self
+            .provider
+            .get_transaction_receipt(transaction_hash)
+            .await?;
+        Ok(receipt.receipt.execution_result().status())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use starknet::{
-        accounts::ExecutionEncoding, core::chain_id, providers::Url, signers::SigningKey,
-    };
-
     use super::*;
     use dotenv::dotenv;
+    use starknet::core::types::StarknetError;
     use std::env;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
 
     fn setup() -> HashingProvider {
         dotenv().ok();
@@ -213,7 +559,8 @@ mod tests {
             provider.clone(),
             signer,
             signer_address,
-            chain_id::SEPOLIA,
+            parse_chain_id(&env::var("STARKNET_CHAIN_ID").unwrap_or("SEPOLIA".to_string()))
+                .unwrap(),
             ExecutionEncoding::New,
         );
 
@@ -229,6 +576,413 @@ mod tests {
         )
     }
 
+    fn offline_provider() -> HashingProvider {
+        HashingProvider::connect(
+            "http://localhost:5050",
+            Felt::ONE,
+            Felt::TWO,
+            Felt::ONE,
+            Felt::ONE,
+            chain_id::SEPOLIA,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_parse_chain_id() {
+        assert_eq!(parse_chain_id("SEPOLIA").unwrap(), chain_id::SEPOLIA);
+        assert_eq!(parse_chain_id("mainnet").unwrap(), chain_id::MAINNET);
+        assert_eq!(parse_chain_id("0x1234").unwrap(), Felt::from(0x1234));
+        for value in ["", "GOERLI", "0xnothex", "1234"] {
+            assert!(
+                parse_chain_id(value).is_err(),
+                "Expected {:?} to be rejected",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn should_sign_for_configured_chain() {
+        for (name, expected) in [
+            ("SEPOLIA", chain_id::SEPOLIA),
+            ("MAINNET", chain_id::MAINNET),
+            ("0x1234", Felt::from(0x1234)),
+        ] {
+            let hashing = HashingProvider::connect(
+                "http://localhost:5050",
+                Felt::ONE,
+                Felt::TWO,
+                Felt::ONE,
+                Felt::ONE,
+                parse_chain_id(name).unwrap(),
+            )
+            .unwrap();
+
+            assert_eq!(hashing.chain_id(), expected, "chain {}", name);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_rpc_url() {
+        assert!(
+            HashingProvider::connect(
+                "not a url",
+                Felt::ONE,
+                Felt::TWO,
+                Felt::ONE,
+                Felt::ONE,
+                chain_id::SEPOLIA,
+            )
+            .is_err()
+        );
+    }
+
+    /// Answers every `starknet_call` with a single fee of 42, recording the block id each call
+    /// was made against.
+    async fn stub_node() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let block_ids = Arc::new(Mutex::new(Vec::new()));
+        let recorded = block_ids.clone();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let recorded = recorded.clone();
+                async move {
+                    let params = &body["params"];
+                    let block_id = params.get("block_id").or_else(|| params.get(1)).cloned();
+                    recorded.lock().unwrap().push(block_id.unwrap_or_default());
+                    axum::Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": body["id"],
+                        "result": ["0x1", "0x2a"],
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (url, block_ids)
+    }
+
+    fn proof_request(at_block_hash: Option<&str>) -> RequestProof {
+        RequestProof {
+            job_id: "twap".to_string(),
+            job_group_id: None,
+            start_timestamp: 0,
+            end_timestamp: 3600,
+            priority: None,
+            tag: None,
+            deadline_ts: None,
+            settlement_timestamp: None,
+            timeout_secs: None,
+            at_block_hash: at_block_hash.map(str::to_string),
+            ranges: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_read_fees_at_the_block_a_request_pins() {
+        let (url, block_ids) = stub_node().await;
+        let block_hash = "0x4d893935543cb5ad8cf8c6b6e1ab9e5b4ef1c1b4e5d6f3a0b9c8d7e6f5a4b3c";
+        let connect = || {
+            HashingProvider::connect(
+                &url,
+                Felt::ONE,
+                Felt::TWO,
+                Felt::ONE,
+                Felt::ONE,
+                chain_id::SEPOLIA,
+            )
+            .unwrap()
+        };
+
+        for (at_block_hash, expected) in [
+            (None, serde_json::json!("latest")),
+            (
+                Some(block_hash),
+                serde_json::json!({ "block_hash": block_hash }),
+            ),
+        ] {
+            let hashing = connect()
+                .for_request(&proof_request(at_block_hash))
+                .unwrap();
+            assert_eq!(
+                hashing
+                    .get_avg_fees_in_range(0, 3600, &RetryBudget::default())
+                    .await
+                    .unwrap(),
+                vec![42.0]
+            );
+            assert_eq!(block_ids.lock().unwrap().pop(), Some(expected));
+        }
+
+        // Stored hashes are read at the pinned block too
+        let hashing = connect().with_block_hash(block_hash).unwrap();
+        assert!(hashing.get_hash_stored_avg_fees(0).await.is_err());
+        assert_eq!(
+            block_ids.lock().unwrap().pop(),
+            Some(serde_json::json!({ "block_hash": block_hash }))
+        );
+    }
+
+    #[test]
+    fn should_reject_request_with_invalid_block_hash() {
+        assert!(
+            offline_provider()
+                .for_request(&proof_request(Some("0xnothex")))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_block_hash() {
+        for block_hash in [
+            "",
+            "0x",
+            "4d8939",
+            "0xnothex",
+            "0x4d893935543cb5ad8cf8c6b6e1ab9e5b4ef1c1b4e5d6f3a0b9c8d7e6f5a4b3c00",
+        ] {
+            assert!(
+                parse_block_hash(block_hash).is_err(),
+                "Expected {:?} to be rejected",
+                block_hash
+            );
+        }
+    }
+
+    #[test]
+    fn should_convert_felts_within_exact_f64_range() {
+        assert_eq!(try_convert_felt_to_f64(Felt::from(1234u64)), Ok(1234.0));
+        assert_eq!(
+            try_convert_felt_to_f64(Felt::from(MAX_EXACT_F64_INT - 1)),
+            Ok((MAX_EXACT_F64_INT - 1) as f64)
+        );
+    }
+
+    #[test]
+    fn should_reject_felts_beyond_exact_f64_range() {
+        let felt = Felt::from(u128::MAX);
+        assert_eq!(
+            try_convert_felt_to_f64(felt),
+            Err(FeltConversionError(felt))
+        );
+        assert_eq!(
+            FeltConversionError(Felt::from(MAX_EXACT_F64_INT + 1)).to_string(),
+            "Felt 0x20000000000001 exceeds 2^53 and can't be represented exactly as an f64"
+        );
+    }
+
+    #[test]
+    fn should_reject_range_with_unconvertible_fee() {
+        let fees = [Felt::ONE, Felt::TWO, Felt::THREE];
+        assert_eq!(try_convert_avg_fees(&fees).unwrap(), vec![1.0, 2.0, 3.0]);
+
+        // A fee in the middle of the range fails the whole range instead of leaving a gap
+        let unconvertible = Felt::from(u128::MAX);
+        let err = try_convert_avg_fees(&[Felt::ONE, unconvertible, Felt::THREE]).unwrap_err();
+        match err {
+            ProviderError::StarknetError(StarknetError::UnexpectedError(message)) => {
+                assert_eq!(message, FeltConversionError(unconvertible).to_string())
+            }
+            other => panic!("Expected a conversion error, got {:?}", other),
+        }
+        assert!(!is_transient(&ProviderError::StarknetError(
+            StarknetError::UnexpectedError(String::new())
+        )));
+    }
+
+    #[test]
+    fn should_decode_length_prefixed_fees() {
+        let felts = vec![Felt::from(3u8), Felt::ONE, Felt::TWO, Felt::THREE];
+        assert_eq!(
+            FeeResponseLayout::LengthPrefixed.decode(felts).unwrap(),
+            vec![Felt::ONE, Felt::TWO, Felt::THREE]
+        );
+        assert!(matches!(
+            FeeResponseLayout::LengthPrefixed.decode(vec![]),
+            Err(ProviderError::ArrayLengthMismatch)
+        ));
+    }
+
+    #[test]
+    fn should_decode_raw_fees() {
+        let felts = vec![Felt::ONE, Felt::TWO, Felt::THREE];
+        assert_eq!(FeeResponseLayout::Raw.decode(felts.clone()).unwrap(), felts);
+        assert!(FeeResponseLayout::Raw.decode(vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_parse_fee_response_layout() {
+        assert_eq!(
+            "LENGTH_PREFIXED".parse::<FeeResponseLayout>().unwrap(),
+            FeeResponseLayout::LengthPrefixed
+        );
+        assert_eq!(
+            "raw".parse::<FeeResponseLayout>().unwrap(),
+            FeeResponseLayout::Raw
+        );
+        assert!("struct".parse::<FeeResponseLayout>().is_err());
+        assert_eq!(
+            offline_provider().fee_response_layout(),
+            FeeResponseLayout::LengthPrefixed
+        );
+    }
+
+    #[test]
+    fn should_hash_avg_fees_like_the_contract() {
+        // SHA-256 of no input
+        assert_eq!(
+            StoredHash::of_avg_fees(&[]),
+            StoredHash([
+                0xe3b0c442, 0x98fc1c14, 0x9afbf4c8, 0x996fb924, 0x27ae41e4, 0x649b934c, 0xa495991b,
+                0x7852b855,
+            ])
+        );
+        assert_eq!(
+            StoredHash::of_avg_fees(&[30_000_000_000.0, 30_000_007_919.0, 30_000_015_838.0]),
+            StoredHash([
+                0x464ef197, 0x74c9aeee, 0x20c67cbc, 0x857bc535, 0x45a60b90, 0xcb6bf3ef, 0xbfc9f2a8,
+                0x2e2ce25e,
+            ])
+        );
+    }
+
+    #[test]
+    fn should_detect_zero_hash() {
+        assert!(StoredHash::default().is_zero());
+        assert!(!StoredHash([0, 0, 0, 0, 0, 0, 0, 1]).is_zero());
+    }
+
+    #[test]
+    fn should_read_hash_from_felts() {
+        let felts: Vec<Felt> = (1..=8u32).map(Felt::from).collect();
+        assert_eq!(
+            StoredHash::from_felts(&felts).unwrap(),
+            StoredHash([1, 2, 3, 4, 5, 6, 7, 8])
+        );
+
+        for len in [0, 7, 9] {
+            assert!(matches!(
+                StoredHash::from_felts(&vec![Felt::ONE; len]),
+                Err(ProviderError::ArrayLengthMismatch)
+            ));
+        }
+    }
+
+    #[test]
+    fn should_format_hash_as_hex() {
+        let hash = StoredHash([0xdeadbeef, 1, 0, 0, 0, 0, 0, 0xffffffff]);
+        assert_eq!(
+            hash.to_string(),
+            "0xdeadbeef000000010000000000000000000000000000000000000000ffffffff"
+        );
+        assert_eq!(
+            StoredHash::default().to_string(),
+            format!("0x{}", "0".repeat(64))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_treat_unreachable_node_as_transient() {
+        // Nothing listens on port 1, so the connection is refused
+        let error = HashingProvider::connect(
+            "http://127.0.0.1:1",
+            Felt::ONE,
+            Felt::TWO,
+            Felt::ONE,
+            Felt::ONE,
+            chain_id::SEPOLIA,
+        )
+        .unwrap()
+        .with_max_retries(0)
+        .get_avg_fees_in_range(0, 3600, &RetryBudget::default())
+        .await
+        .unwrap_err();
+
+        assert!(is_transient(&error), "Expected {:?} to be transient", error);
+        assert!(is_transient(&ProviderError::RateLimited));
+        assert!(!is_transient(&ProviderError::StarknetError(
+            StarknetError::ContractNotFound
+        )));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_retry_transient_errors_with_backoff() {
+        let attempts = AtomicU32::new(0);
+        let started = tokio::time::Instant::now();
+
+        let result = retry_transient(MAX_RETRIES, &RetryBudget::default(), || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(ProviderError::RateLimited),
+                _ => Ok(vec![Felt::ONE]),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), vec![Felt::ONE]);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        // 1s before the first retry, 2s before the second
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_give_up_after_max_retries() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = retry_transient(2, &RetryBudget::default(), || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(ProviderError::RateLimited)
+        })
+        .await;
+
+        assert!(matches!(result, Err(ProviderError::RateLimited)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_take_retries_from_budget() {
+        let attempts = AtomicU32::new(0);
+        let retry_budget = RetryBudget::new(1);
+
+        let result: Result<(), _> = retry_transient(MAX_RETRIES, &retry_budget, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(ProviderError::RateLimited)
+        })
+        .await;
+
+        assert!(matches!(result, Err(ProviderError::RateLimited)));
+        // The single retry the budget allows, then none
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(retry_budget.remaining(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_fail_fast_on_deterministic_errors() {
+        let attempts = AtomicU32::new(0);
+        let started = tokio::time::Instant::now();
+
+        let result: Result<(), _> =
+            retry_transient(MAX_RETRIES, &RetryBudget::default(), || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(ProviderError::StarknetError(
+                    StarknetError::ContractNotFound,
+                ))
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ProviderError::StarknetError(
+                StarknetError::ContractNotFound
+            ))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
     #[ignore = "calling actual rpc node"]
     #[tokio::test]
     async fn should_retrieve_avg_fees_in_range() {
@@ -236,7 +990,7 @@ mod tests {
 
         let avg_fees = hashing
             // .get_avg_fees_in_range(1739304000, 1739307600)
-            .get_avg_fees_in_range(1734843600, 1742533200)
+            .get_avg_fees_in_range(1734843600, 1742533200, &RetryBudget::default())
             .await
             .unwrap();
 
@@ -258,6 +1012,22 @@ mod tests {
         println!("{:?}", hash);
     }
 
+    #[ignore = "calling actual rpc node"]
+    #[tokio::test]
+    async fn should_match_stored_hash_of_avg_fees() {
+        let hashing = setup();
+        let timestamp = 1739307600;
+
+        let stored = hashing.get_hash_stored_avg_fees(timestamp).await.unwrap();
+        let avg_fees = hashing
+            .get_avg_fees_in_range(timestamp, timestamp + 179 * 3600, &RetryBudget::default())
+            .await
+            .unwrap();
+
+        assert!(!stored.is_zero());
+        assert_eq!(StoredHash::of_avg_fees(&avg_fees), stored);
+    }
+
     #[ignore = "calling actual rpc node"]
     #[tokio::test]
     async fn should_get_hash_batched_avg_fees() {
@@ -1,9 +1,18 @@
 #![deny(unused_crate_dependencies)]
 use dotenv as _;
 use tracing_subscriber as _;
+// Only used by the benchmarks
+#[cfg(test)]
+use criterion as _;
 
+pub mod admin;
+pub mod env_util;
 pub mod hashing;
+pub mod metrics;
 pub mod proof_composition;
 pub mod queue;
 pub mod response_handler;
 pub mod services;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod time;
@@ -1,20 +1,38 @@
 use aws_config::{BehaviorVersion, defaults};
 use db::DbConnection;
 use eyre::Result;
-use message_handler::proof_composition::BonsaiProofProvider;
+use message_handler::admin::admin_router;
+use message_handler::env_util::{
+    bool_env, duration_secs_env, optional_env, parse_env, required_env, u64_env,
+};
+use message_handler::metrics::metrics_router;
+use message_handler::proof_composition::host_cache::{
+    DEFAULT_HOST_CACHE_SIZE, HostComputationCache,
+};
+use message_handler::proof_composition::{
+    BonsaiConfig, BonsaiProofProvider, check_provider_health,
+};
 use message_handler::queue::sqs_message_queue::SqsMessageQueue;
-use message_handler::services::proof_job_handler::ProofJobHandler;
+use message_handler::services::proof_job_handler::{
+    DEFAULT_CANCEL_TTL, DEFAULT_MAX_CONCURRENT_PROOFS, DEFAULT_MAX_LOG_BODY_LEN,
+    DEFAULT_MAX_PROOF_GENERATION_TIMEOUT, InvalidMessagePolicy, ProofJobHandler,
+    VerificationFailurePolicy,
+};
+use message_handler::time::ETHEREUM_BLOCK_TIME_SECS;
+use std::net::SocketAddr;
 use std::sync::{Arc, atomic::AtomicBool};
 use tokio::signal;
-use tokio::time::{Duration, sleep};
-use tracing::{Level, debug, error, info, warn};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{Level, debug, info, warn};
 use tracing_subscriber::FmtSubscriber;
 
-const MAX_DB_RETRY_ATTEMPTS: u32 = 5;
-const DB_RETRY_DELAY_MS: u64 = 2000;
+const DEFAULT_DB_CONNECT_RETRIES: u64 = 5;
+const DEFAULT_DB_CONNECT_BACKOFF_MS: u64 = 2000;
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(600);
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     // Initialize tracing with INFO level default
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
@@ -27,11 +45,35 @@ async fn main() -> Result<()> {
     // Load .env file
     dotenv::dotenv().ok();
 
+    // The prover reads the Bonsai settings from the environment, which may only be written
+    // before the runtime starts any threads
+    let bonsai_config = match (
+        optional_env("BONSAI_API_URL"),
+        optional_env("BONSAI_API_KEY"),
+    ) {
+        (Some(api_url), Some(api_key)) => {
+            let bonsai_config = BonsaiConfig { api_url, api_key };
+            // SAFETY: no other thread has been started yet
+            unsafe { bonsai_config.install()? };
+            info!("Proving on Bonsai at {}", bonsai_config.api_url);
+            Some(bonsai_config)
+        }
+        _ => {
+            info!("No Bonsai settings, proving locally");
+            None
+        }
+    };
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(bonsai_config))
+}
+
+async fn run(bonsai_config: Option<BonsaiConfig>) -> Result<()> {
     // Get the queue URL from environment variable
-    let queue_url = std::env::var("SQS_QUEUE_URL")
-        .map_err(|e| eyre::eyre!("SQS_QUEUE_URL environment variable not set: {}", e))?;
-    let database_url = std::env::var("DATABASE_URL")
-        .map_err(|e| eyre::eyre!("DATABASE_URL environment variable not set: {}", e))?;
+    let queue_url = required_env("SQS_QUEUE_URL")?;
+    let database_url = required_env("DATABASE_URL")?;
     info!("Using SQS Queue URL: {}", queue_url);
     info!("Using database URL: {}", database_url);
 
@@ -39,80 +81,220 @@ async fn main() -> Result<()> {
     // This will respect AWS_ENDPOINT_URL from the .env file
     let config = defaults(BehaviorVersion::latest()).load().await;
     info!("AWS configuration loaded");
-    let queue = Arc::new(SqsMessageQueue::new(queue_url, config));
+    let mut queue = SqsMessageQueue::new(queue_url, config.clone());
+    if let Some(max_messages) = parse_env::<i32>("SQS_MAX_MESSAGES")? {
+        queue = queue.with_max_messages(max_messages);
+    }
+    if let Some(wait_time_seconds) = parse_env::<i32>("SQS_WAIT_TIME_SECONDS")? {
+        queue = queue.with_wait_time_seconds(wait_time_seconds);
+    }
+    info!(
+        "Receiving up to {} messages per poll, waiting up to {}s",
+        queue.max_messages(),
+        queue.wait_time_seconds()
+    );
+    let queue = Arc::new(queue);
 
-    // Attempt database connection with retries
-    let db = connect_to_database_with_retry(&database_url, MAX_DB_RETRY_ATTEMPTS).await?;
+    let dead_letter_queue = optional_env("SQS_DEAD_LETTER_QUEUE_URL").map(|url| {
+        info!("Using SQS dead-letter queue URL: {}", url);
+        Arc::new(SqsMessageQueue::new(url, config.clone()))
+    });
+    let output_queue = optional_env("SQS_OUTPUT_QUEUE_URL").map(|url| {
+        info!("Using SQS output queue URL: {}", url);
+        Arc::new(SqsMessageQueue::new(url, config.clone()))
+    });
+    let invalid_message_policy =
+        parse_env::<InvalidMessagePolicy>("INVALID_MESSAGE_POLICY")?.unwrap_or_default();
+    info!("Using invalid message policy: {:?}", invalid_message_policy);
+    let verification_failure_policy =
+        parse_env::<VerificationFailurePolicy>("VERIFICATION_FAILURE_POLICY")?.unwrap_or_default();
+    info!(
+        "Using verification failure policy: {:?}",
+        verification_failure_policy
+    );
+    let max_job_failures = parse_env::<u64>("MAX_JOB_FAILURES")?;
+    let idle_shutdown = parse_env::<u64>("IDLE_SHUTDOWN_SECS")?.map(Duration::from_secs);
+    let drain_timeout = duration_secs_env("DRAIN_TIMEOUT_SECS", DEFAULT_DRAIN_TIMEOUT)?;
+    let max_concurrent_proofs = u64_env(
+        "MAX_CONCURRENT_PROOFS",
+        DEFAULT_MAX_CONCURRENT_PROOFS as u64,
+    )? as usize;
+    if max_concurrent_proofs == 0 {
+        return Err(eyre::eyre!("MAX_CONCURRENT_PROOFS must be at least 1"));
+    }
+    let cancel_ttl = duration_secs_env("CANCEL_TTL_SECS", DEFAULT_CANCEL_TTL)?;
+    let expected_block_time_secs = u64_env("EXPECTED_BLOCK_TIME_SECS", ETHEREUM_BLOCK_TIME_SECS)?;
+    let log_message_bodies = bool_env("LOG_MESSAGE_BODIES", false)?;
+    let max_log_body_len = u64_env("MAX_LOG_BODY_LEN", DEFAULT_MAX_LOG_BODY_LEN as u64)? as usize;
+    let proof_generation_timeout =
+        duration_secs_env("PROOF_GENERATION_TIMEOUT_SECS", Duration::from_secs(300))?;
+    let max_proof_generation_timeout = duration_secs_env(
+        "MAX_PROOF_GENERATION_TIMEOUT_SECS",
+        DEFAULT_MAX_PROOF_GENERATION_TIMEOUT,
+    )?;
+    let allow_degraded = bool_env("ALLOW_DEGRADED", false)?;
+    let reserve_price_cache_size =
+        u64_env("RESERVE_PRICE_CACHE_SIZE", DEFAULT_HOST_CACHE_SIZE as u64)? as usize;
+
+    // Wait for the database to come up, as it may still be starting alongside this service
+    let db_connect_retries = u64_env("DB_CONNECT_RETRIES", DEFAULT_DB_CONNECT_RETRIES)? as u32;
+    let db_connect_backoff = Duration::from_millis(u64_env(
+        "DB_CONNECT_BACKOFF_MS",
+        DEFAULT_DB_CONNECT_BACKOFF_MS,
+    )?);
+    let db =
+        DbConnection::connect_with_retry(&database_url, db_connect_retries, db_connect_backoff)
+            .await?;
+    info!("Successfully connected to the database");
+
+    // Receipts may live in a database of their own, separate from the indexer's
+    let receipt_db = match optional_env("RECEIPT_DATABASE_URL") {
+        Some(receipt_database_url) => {
+            let receipt_db = DbConnection::connect_with_retry(
+                &receipt_database_url,
+                db_connect_retries,
+                db_connect_backoff,
+            )
+            .await?;
+            receipt_db.migrate().await?;
+            info!("Storing proof receipts in the receipt database");
+            Some(receipt_db)
+        }
+        None => None,
+    };
 
     let terminator = Arc::new(AtomicBool::new(false));
 
-    let proof_provider = Arc::new(BonsaiProofProvider::new());
+    let mut proof_provider = BonsaiProofProvider::new();
+    if let Some(bonsai_config) = bonsai_config {
+        proof_provider = proof_provider.with_config(bonsai_config);
+    }
+    if reserve_price_cache_size > 0 {
+        proof_provider = proof_provider.with_reserve_price_cache(Arc::new(
+            HostComputationCache::new(reserve_price_cache_size),
+        ));
+    }
+    check_provider_health(&proof_provider, allow_degraded).await?;
+    let proof_provider = Arc::new(proof_provider);
 
-    let processor = ProofJobHandler::new(
+    let mut processor = ProofJobHandler::new(
         queue.clone(),
-        terminator.clone(),
+        terminator,
         db.clone(),
         proof_provider,
-        std::time::Duration::from_secs(300), // 5 minutes timeout for proof generation
-    );
+        proof_generation_timeout,
+    )
+    .with_invalid_message_policy(invalid_message_policy)
+    .with_verification_failure_policy(verification_failure_policy)
+    .with_max_proof_generation_timeout(max_proof_generation_timeout)
+    .with_max_concurrent_proofs(max_concurrent_proofs)
+    .with_cancel_ttl(cancel_ttl)
+    .with_expected_block_time_secs(expected_block_time_secs)
+    .with_log_message_bodies(log_message_bodies)
+    .with_max_log_body_len(max_log_body_len);
+    if let Some(dead_letter_queue) = dead_letter_queue {
+        processor = processor.with_dead_letter_queue(dead_letter_queue);
+    }
+    if let Some(max_job_failures) = max_job_failures {
+        info!("Giving up on jobs after {} failures", max_job_failures);
+        processor = processor.with_max_failures(max_job_failures);
+    }
+    if let Some(output_queue) = output_queue {
+        processor = processor.with_output_queue(output_queue);
+    }
+    if let Some(receipt_db) = receipt_db {
+        processor = processor.with_receipt_db(receipt_db);
+    }
+    if let Some(idle_shutdown) = idle_shutdown {
+        info!("Shutting down after {:?} without messages", idle_shutdown);
+        processor = processor.with_idle_shutdown(idle_shutdown);
+    }
+
+    let processor = Arc::new(processor);
+
+    // Serve the job metrics for scraping, and the admin routes pausing and resuming the
+    // processor, if asked to. The server stops once told to on shutdown, finishing the
+    // requests it is answering
+    let metrics_server = match parse_env::<SocketAddr>("METRICS_ADDR")? {
+        Some(metrics_addr) => {
+            let listener = tokio::net::TcpListener::bind(metrics_addr).await?;
+            info!("Serving metrics and admin routes on {}", metrics_addr);
+            let app = metrics_router(processor.job_metrics(), vec![processor.jobs_in_flight()])
+                .merge(admin_router(processor.clone()));
+            let (stop, stopped) = oneshot::channel::<()>();
+            let handle = tokio::spawn(async move {
+                axum::serve(listener, app)
+                    .with_graceful_shutdown(async {
+                        stopped.await.ok();
+                    })
+                    .await
+            });
+            Some((stop, handle))
+        }
+        None => None,
+    };
 
     // Start the job processor in a separate task
-    let processor_handle = tokio::spawn(async move {
-        // Run once - the receive_job method has its own loop
-        if let Err(e) = processor.receive_job().await {
-            debug!("Job processor exited with error: {:?}", e);
+    let mut processor_handle = tokio::spawn({
+        let processor = processor.clone();
+        async move {
+            // Run once - the receive_job method has its own loop
+            if let Err(e) = processor.receive_job().await {
+                debug!("Job processor exited with error: {:?}", e);
+            }
         }
     });
 
-    // Handle Ctrl+C for graceful shutdown
+    // Handle Ctrl+C for graceful shutdown, unless the processor stops on its own first
     info!("Waiting for shutdown signal...");
-    signal::ctrl_c().await?;
-    info!("Received shutdown signal, initiating graceful shutdown...");
+    tokio::select! {
+        result = signal::ctrl_c() => {
+            result?;
+            info!("Received shutdown signal, initiating graceful shutdown...");
+        }
+        _ = &mut processor_handle => {
+            info!("Job processor stopped");
+            stop_metrics_server(metrics_server).await;
+            return Ok(());
+        }
+    }
 
-    // Set the terminator flag
-    terminator.store(true, std::sync::atomic::Ordering::Relaxed);
+    // Stop taking jobs and let the ones in flight finish, only abandoning them once the drain
+    // timeout is up
+    processor.drain();
+    info!(
+        "Waiting up to {:?} for jobs in flight to finish...",
+        drain_timeout
+    );
+    if tokio::time::timeout(drain_timeout, &mut processor_handle)
+        .await
+        .is_err()
+    {
+        warn!(
+            "Jobs still running after {:?}, abandoning them",
+            drain_timeout
+        );
+        processor_handle.abort();
+    }
 
-    // Wait for the processor to finish
-    info!("Waiting for processor to finish...");
-    let _ = processor_handle.await;
+    stop_metrics_server(metrics_server).await;
 
     info!("Shutdown complete");
     Ok(())
 }
 
-/// Attempts to connect to the database with retry logic
-async fn connect_to_database_with_retry(
-    database_url: &str,
-    max_attempts: u32,
-) -> Result<Arc<DbConnection>> {
-    let mut attempt = 1;
-
-    loop {
-        info!(
-            "Attempting database connection (attempt {}/{})",
-            attempt, max_attempts
-        );
+/// Tells the metrics server, if any, to stop and waits for it to finish the requests in flight.
+async fn stop_metrics_server(
+    metrics_server: Option<(oneshot::Sender<()>, JoinHandle<std::io::Result<()>>)>,
+) {
+    let Some((stop, handle)) = metrics_server else {
+        return;
+    };
 
-        match DbConnection::new(database_url).await {
-            Ok(conn) => {
-                info!("Successfully connected to the database");
-                return Ok(conn);
-            }
-            Err(e) => {
-                if attempt >= max_attempts {
-                    error!(
-                        "Failed to connect to database after {} attempts: {}",
-                        max_attempts, e
-                    );
-                    return Err(e);
-                }
-
-                warn!("Database connection attempt {} failed: {}", attempt, e);
-                warn!("Retrying in {} ms...", DB_RETRY_DELAY_MS);
-
-                sleep(Duration::from_millis(DB_RETRY_DELAY_MS)).await;
-                attempt += 1;
-            }
-        }
+    stop.send(()).ok();
+    match handle.await {
+        Ok(Ok(())) => info!("Metrics server stopped"),
+        Ok(Err(e)) => warn!("Metrics server failed: {}", e),
+        Err(e) => warn!("Metrics server task failed: {}", e),
     }
 }
@@ -0,0 +1,290 @@
+use axum::{
+    Router,
+    extract::State,
+    http::{HeaderName, header},
+    routing::get,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A named value that can go up and down, such as the number of jobs currently being processed.
+#[derive(Debug)]
+pub struct Gauge {
+    name: &'static str,
+    value: AtomicI64,
+}
+
+impl Gauge {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: AtomicI64::new(0),
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn inc(&self) {
+        self.value.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn dec(&self) {
+        self.value.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::SeqCst);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    /// Renders the gauge's current value in the Prometheus text format.
+    pub fn to_prometheus(&self) -> String {
+        format!(
+            "# TYPE {name} gauge\n{name} {value}\n",
+            name = self.name,
+            value = self.get()
+        )
+    }
+}
+
+/// A named set of counters keyed by a label, such as the number of failures per job.
+#[derive(Debug)]
+pub struct LabeledCounter {
+    name: &'static str,
+    values: Mutex<HashMap<String, u64>>,
+}
+
+impl LabeledCounter {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            values: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn inc(&self, label: &str) {
+        if let Ok(mut values) = self.values.lock() {
+            *values.entry(label.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn get(&self, label: &str) -> u64 {
+        self.values
+            .lock()
+            .map(|values| values.get(label).copied().unwrap_or_default())
+            .unwrap_or_default()
+    }
+}
+
+/// How a proof job, or a message that should have been one, ended up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    /// Its proof was generated and sent on.
+    Succeeded,
+    /// Its proof generation failed, produced an invalid receipt or panicked.
+    Failed,
+    /// Its proof generation took longer than its timeout.
+    TimedOut,
+    /// It couldn't be parsed as a job, or its range can't be proven.
+    Invalid,
+    /// It was forwarded to the dead-letter queue.
+    DeadLettered,
+}
+
+/// Tallies of proof job outcomes since the handler started.
+#[derive(Debug, Default)]
+pub struct JobMetrics {
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    timed_out: AtomicU64,
+    invalid: AtomicU64,
+    dead_lettered: AtomicU64,
+}
+
+impl JobMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, outcome: JobOutcome) {
+        let counter = match outcome {
+            JobOutcome::Succeeded => &self.succeeded,
+            JobOutcome::Failed => &self.failed,
+            JobOutcome::TimedOut => &self.timed_out,
+            JobOutcome::Invalid => &self.invalid,
+            JobOutcome::DeadLettered => &self.dead_lettered,
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn snapshot(&self) -> JobMetricsSnapshot {
+        JobMetricsSnapshot {
+            succeeded: self.succeeded.load(Ordering::SeqCst),
+            failed: self.failed.load(Ordering::SeqCst),
+            timed_out: self.timed_out.load(Ordering::SeqCst),
+            invalid: self.invalid.load(Ordering::SeqCst),
+            dead_lettered: self.dead_lettered.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// The values of a [`JobMetrics`] at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobMetricsSnapshot {
+    pub succeeded: u64,
+    pub failed: u64,
+    pub timed_out: u64,
+    pub invalid: u64,
+    pub dead_lettered: u64,
+}
+
+impl JobMetricsSnapshot {
+    /// Renders the tallies in the Prometheus text format, as a `proof_jobs_total` counter
+    /// labelled by outcome.
+    pub fn to_prometheus(&self) -> String {
+        let mut text = String::from(
+            "# HELP proof_jobs_total Proof jobs by outcome.\n# TYPE proof_jobs_total counter\n",
+        );
+        for (outcome, value) in [
+            ("succeeded", self.succeeded),
+            ("failed", self.failed),
+            ("timed_out", self.timed_out),
+            ("invalid", self.invalid),
+            ("dead_lettered", self.dead_lettered),
+        ] {
+            let _ = writeln!(
+                text,
+                "proof_jobs_total{{outcome=\"{}\"}} {}",
+                outcome, value
+            );
+        }
+        text
+    }
+}
+
+/// Metrics served by [`metrics_router`].
+#[derive(Debug, Clone)]
+struct ScrapedMetrics {
+    job_metrics: Arc<JobMetrics>,
+    gauges: Vec<Arc<Gauge>>,
+}
+
+/// Routes `GET /metrics`, rendering `job_metrics` and `gauges` for Prometheus to scrape.
+pub fn metrics_router(job_metrics: Arc<JobMetrics>, gauges: Vec<Arc<Gauge>>) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(ScrapedMetrics {
+            job_metrics,
+            gauges,
+        })
+}
+
+async fn render_metrics(
+    State(metrics): State<ScrapedMetrics>,
+) -> ([(HeaderName, &'static str); 1], String) {
+    let mut body = metrics.job_metrics.snapshot().to_prometheus();
+    for gauge in &metrics.gauges {
+        body.push_str(&gauge.to_prometheus());
+    }
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gauge_inc_dec() {
+        let gauge = Gauge::new("test_gauge");
+        assert_eq!(gauge.name(), "test_gauge");
+        assert_eq!(gauge.get(), 0);
+
+        gauge.inc();
+        gauge.inc();
+        assert_eq!(gauge.get(), 2);
+
+        gauge.dec();
+        assert_eq!(gauge.get(), 1);
+
+        gauge.set(7);
+        assert_eq!(gauge.get(), 7);
+        assert_eq!(
+            gauge.to_prometheus(),
+            "# TYPE test_gauge gauge\ntest_gauge 7\n"
+        );
+    }
+
+    #[test]
+    fn test_labeled_counter_inc() {
+        let counter = LabeledCounter::new("test_counter");
+        assert_eq!(counter.name(), "test_counter");
+        assert_eq!(counter.get("a"), 0);
+
+        counter.inc("a");
+        counter.inc("a");
+        counter.inc("b");
+        assert_eq!(counter.get("a"), 2);
+        assert_eq!(counter.get("b"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_render_metrics_serves_prometheus_text() {
+        let job_metrics = Arc::new(JobMetrics::new());
+        job_metrics.record(JobOutcome::Failed);
+        let jobs_in_flight = Arc::new(Gauge::new("proof_jobs_in_flight"));
+        jobs_in_flight.inc();
+        jobs_in_flight.inc();
+
+        let ([(name, content_type)], body) = render_metrics(State(ScrapedMetrics {
+            job_metrics,
+            gauges: vec![jobs_in_flight],
+        }))
+        .await;
+        assert_eq!(name, header::CONTENT_TYPE);
+        assert_eq!(content_type, "text/plain; version=0.0.4");
+        assert!(body.contains("proof_jobs_total{outcome=\"failed\"} 1\n"));
+        assert!(body.contains("# TYPE proof_jobs_in_flight gauge\nproof_jobs_in_flight 2\n"));
+    }
+
+    #[test]
+    fn test_job_metrics_snapshot_renders_prometheus_text() {
+        let metrics = JobMetrics::new();
+        metrics.record(JobOutcome::Succeeded);
+        metrics.record(JobOutcome::Succeeded);
+        metrics.record(JobOutcome::TimedOut);
+        metrics.record(JobOutcome::DeadLettered);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot,
+            JobMetricsSnapshot {
+                succeeded: 2,
+                timed_out: 1,
+                dead_lettered: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            snapshot.to_prometheus(),
+            "# HELP proof_jobs_total Proof jobs by outcome.\n\
+             # TYPE proof_jobs_total counter\n\
+             proof_jobs_total{outcome=\"succeeded\"} 2\n\
+             proof_jobs_total{outcome=\"failed\"} 0\n\
+             proof_jobs_total{outcome=\"timed_out\"} 1\n\
+             proof_jobs_total{outcome=\"invalid\"} 0\n\
+             proof_jobs_total{outcome=\"dead_lettered\"} 1\n"
+        );
+    }
+}
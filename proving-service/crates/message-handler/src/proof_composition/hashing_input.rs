@@ -0,0 +1,60 @@
+use starknet::core::types::Felt;
+
+/// Builds the felt inputs of the hashing guest, repeating `raw` until `target_len` felts are
+/// produced. Each raw value is hex-parsed once, however often it is repeated.
+pub fn build_hashing_felts(raw: &[String], target_len: usize) -> Vec<Felt> {
+    let parsed: Vec<Felt> = raw
+        .iter()
+        .take(target_len)
+        .map(|value| Felt::from_hex_unchecked(value))
+        .collect();
+
+    parsed.iter().copied().cycle().take(target_len).collect()
+}
+
+/// Reference implementation of [`build_hashing_felts`], parsing every repeated value again.
+pub fn build_hashing_felts_naive(raw: &[String], target_len: usize) -> Vec<Felt> {
+    let mut res = Vec::with_capacity(target_len);
+    for i in 0..target_len {
+        let index = i % raw.len();
+        let felt = Felt::from_hex_unchecked(&raw[index]);
+        res.push(felt);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_input(len: usize) -> Vec<String> {
+        (0..len)
+            .map(|i| format!("0x{:x}", 1_000_000_000 + i * 7_919))
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_naive_loop() {
+        for (raw_len, target_len) in [
+            (1, 5760),
+            (24, 5760),
+            (5759, 5760),
+            (5760, 5760),
+            (8000, 5760),
+        ] {
+            let raw = raw_input(raw_len);
+            assert_eq!(
+                build_hashing_felts(&raw, target_len),
+                build_hashing_felts_naive(&raw, target_len),
+                "Mismatch for {} raw values",
+                raw_len
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_input_builds_nothing() {
+        assert!(build_hashing_felts(&[], 5760).is_empty());
+        assert!(build_hashing_felts(&raw_input(3), 0).is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use risc0_zkvm::Digest;
+use risc0_zkvm::sha::{Impl, Sha256};
+
+/// Number of results kept by a [`HostComputationCache`] unless configured otherwise.
+pub const DEFAULT_HOST_CACHE_SIZE: usize = 16;
+
+/// Results of heavy host-side computations, such as the reserve price, keyed by a digest of
+/// their input so identical input is only computed once. Holds at most `capacity` results,
+/// evicting the oldest first.
+#[derive(Debug)]
+pub struct HostComputationCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    results: HashMap<Digest, Arc<dyn Any + Send + Sync>>,
+    insertion_order: VecDeque<Digest>,
+}
+
+impl HostComputationCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map(|entries| entries.results.len())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the result cached for `key`, running `compute` and caching its result when
+    /// there is none of type `V`. The lock isn't held while computing.
+    pub fn get_or_compute<V, F>(&self, key: Digest, compute: F) -> Arc<V>
+    where
+        V: Any + Send + Sync,
+        F: FnOnce() -> V,
+    {
+        if let Some(cached) = self.get(&key) {
+            return cached;
+        }
+
+        let result = Arc::new(compute());
+        self.insert(key, result.clone());
+        result
+    }
+
+    fn get<V: Any + Send + Sync>(&self, key: &Digest) -> Option<Arc<V>> {
+        let entries = self.entries.lock().ok()?;
+        entries.results.get(key)?.clone().downcast().ok()
+    }
+
+    fn insert(&self, key: Digest, result: Arc<dyn Any + Send + Sync>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        if entries.results.insert(key, result).is_none() {
+            entries.insertion_order.push_back(key);
+        }
+        while entries.results.len() > self.capacity {
+            let Some(oldest) = entries.insertion_order.pop_front() else {
+                break;
+            };
+            entries.results.remove(&oldest);
+        }
+    }
+}
+
+impl Default for HostComputationCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_HOST_CACHE_SIZE)
+    }
+}
+
+/// Cache key of the reserve price computed from hourly `data` starting at `start_timestamp`.
+pub fn reserve_price_key(data: &[f64], start_timestamp: i64) -> Digest {
+    let mut bytes = Vec::with_capacity(8 + data.len() * 8);
+    bytes.extend_from_slice(&start_timestamp.to_le_bytes());
+    for value in data {
+        bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+    }
+    *Impl::hash_bytes(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Stands in for the reserve price computation, counting how often it runs.
+    fn compute_reserve_price(calls: &AtomicU32, data: &[f64]) -> f64 {
+        calls.fetch_add(1, Ordering::SeqCst);
+        data.iter().sum::<f64>() / data.len() as f64
+    }
+
+    #[test]
+    fn test_identical_data_skips_recomputation() {
+        let cache = HostComputationCache::default();
+        let calls = AtomicU32::new(0);
+        let data = vec![1.0, 2.0, 3.0];
+
+        let first = cache.get_or_compute(reserve_price_key(&data, 1000), || {
+            compute_reserve_price(&calls, &data)
+        });
+        let second = cache.get_or_compute(reserve_price_key(&data.clone(), 1000), || {
+            compute_reserve_price(&calls, &data)
+        });
+
+        assert_eq!(*first, 2.0);
+        assert_eq!(*second, 2.0);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_different_data_is_recomputed() {
+        let cache = HostComputationCache::default();
+        let calls = AtomicU32::new(0);
+        let data = vec![1.0, 2.0, 3.0];
+        let other_data = vec![1.0, 2.0, 4.0];
+
+        cache.get_or_compute(reserve_price_key(&data, 1000), || {
+            compute_reserve_price(&calls, &data)
+        });
+        cache.get_or_compute(reserve_price_key(&other_data, 1000), || {
+            compute_reserve_price(&calls, &other_data)
+        });
+        // Same data, shifted in time
+        cache.get_or_compute(reserve_price_key(&data, 4600), || {
+            compute_reserve_price(&calls, &data)
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_oldest_result_is_evicted() {
+        let cache = HostComputationCache::new(2);
+        let calls = AtomicU32::new(0);
+        let datasets = [vec![1.0], vec![2.0], vec![3.0]];
+
+        for data in &datasets {
+            cache.get_or_compute(reserve_price_key(data, 0), || {
+                compute_reserve_price(&calls, data)
+            });
+        }
+        assert_eq!(cache.len(), 2);
+
+        // The first dataset was evicted, the last one is still cached
+        cache.get_or_compute(reserve_price_key(&datasets[0], 0), || {
+            compute_reserve_price(&calls, &datasets[0])
+        });
+        cache.get_or_compute(reserve_price_key(&datasets[2], 0), || {
+            compute_reserve_price(&calls, &datasets[2])
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_zero_capacity_disables_caching() {
+        let cache = HostComputationCache::new(0);
+        let calls = AtomicU32::new(0);
+        let data = vec![1.0];
+
+        for _ in 0..2 {
+            cache.get_or_compute(reserve_price_key(&data, 0), || {
+                compute_reserve_price(&calls, &data)
+            });
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(feature = "proof-composition")]
+use coprocessor_core::ProofCompositionOutput;
+use eyre::{Result, eyre};
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+
+/// Values a proof composition receipt commits to in its journal.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProvenValues {
+    pub twap: f64,
+    pub reserve_price: f64,
+    pub max_return: f64,
+}
+
+// The journal decoder panics on a journal that isn't a whole number of words
+fn check_word_aligned(receipt: &Receipt) -> Result<()> {
+    let len = receipt.journal.bytes.len();
+    if !len.is_multiple_of(4) {
+        return Err(eyre!(
+            "Failed to decode journal: {} bytes is not a whole number of words",
+            len
+        ));
+    }
+
+    Ok(())
+}
+
+impl ProvenValues {
+    /// Decodes the proven values from the receipt's journal.
+    #[cfg(feature = "proof-composition")]
+    pub fn decode(receipt: &Receipt) -> Result<Self> {
+        check_word_aligned(receipt)?;
+        let output: ProofCompositionOutput = receipt
+            .journal
+            .decode()
+            .map_err(|e| eyre!("Failed to decode journal: {}", e))?;
+
+        Ok(Self {
+            twap: output.twap_result,
+            reserve_price: output.reserve_price,
+            max_return: output.max_return,
+        })
+    }
+
+    /// Decodes the proven values from the receipt's journal.
+    #[cfg(not(feature = "proof-composition"))]
+    pub fn decode(receipt: &Receipt) -> Result<Self> {
+        check_word_aligned(receipt)?;
+        receipt
+            .journal
+            .decode()
+            .map_err(|e| eyre!("Failed to decode journal: {}", e))
+    }
+
+    /// Rejects values no sound proof could produce: non-finite, a negative TWAP or a
+    /// non-positive reserve price.
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("TWAP", self.twap),
+            ("reserve price", self.reserve_price),
+            ("max return", self.max_return),
+        ] {
+            if !value.is_finite() {
+                return Err(eyre!("Proven {} {} is not finite", name, value));
+            }
+        }
+
+        if self.twap < 0.0 {
+            return Err(eyre!("Proven TWAP {} is negative", self.twap));
+        }
+        if self.reserve_price <= 0.0 {
+            return Err(eyre!(
+                "Proven reserve price {} is not positive",
+                self.reserve_price
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::{Digest, FakeReceipt, InnerReceipt, MaybePruned};
+
+    fn receipt_with_journal(journal: Vec<u8>) -> Receipt {
+        let fake_receipt = FakeReceipt::new(MaybePruned::Pruned(Digest::ZERO));
+        Receipt::new(InnerReceipt::Fake(fake_receipt), journal)
+    }
+
+    #[cfg(not(feature = "proof-composition"))]
+    fn encode(values: &ProvenValues) -> Vec<u8> {
+        risc0_zkvm::serde::to_vec(values)
+            .unwrap()
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+            .collect()
+    }
+
+    const VALID: ProvenValues = ProvenValues {
+        twap: 12.5,
+        reserve_price: 3.25,
+        max_return: 0.4,
+    };
+
+    #[cfg(not(feature = "proof-composition"))]
+    #[test]
+    fn test_decode_round_trip() {
+        let receipt = receipt_with_journal(encode(&VALID));
+        assert_eq!(ProvenValues::decode(&receipt).unwrap(), VALID);
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_journal() {
+        // Too short, and not a whole number of words
+        for journal in [vec![0; 4], vec![1, 2, 3]] {
+            let receipt = receipt_with_journal(journal);
+            let err = ProvenValues::decode(&receipt).unwrap_err();
+            assert!(err.to_string().contains("Failed to decode journal"));
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(VALID.validate().is_ok());
+
+        for invalid in [
+            ProvenValues {
+                reserve_price: 0.0,
+                ..VALID
+            },
+            ProvenValues {
+                twap: -1.0,
+                ..VALID
+            },
+            ProvenValues {
+                max_return: f64::NAN,
+                ..VALID
+            },
+            ProvenValues {
+                reserve_price: f64::INFINITY,
+                ..VALID
+            },
+        ] {
+            assert!(
+                invalid.validate().is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::time::{HOUR_SECS, ProofTimestampRanges, expected_fee_points};
 #[cfg(feature = "proof-composition")]
 use add_twap_7d_error_bound_floating::add_twap_7d_error_bound;
 #[cfg(feature = "proof-composition")]
@@ -29,35 +30,609 @@ use remove_seasonality_error_bound_floating::remove_seasonality_error_bound;
 #[cfg(not(feature = "proof-composition"))]
 use risc0_zkvm::Receipt;
 #[cfg(feature = "proof-composition")]
-use risc0_zkvm::{ExecutorEnv, ProverOpts, Receipt, ReceiptKind, default_prover};
+use risc0_zkvm::{ExecutorEnv, ProverOpts, Receipt, ReceiptKind, VerifierContext, default_prover};
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "proof-composition")]
 use simulate_price_verify_position_floating::simulate_price_verify_position;
-#[cfg(feature = "proof-composition")]
-use starknet::core::types::Felt;
+use starknet::providers::Url;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::net::TcpStream;
 #[cfg(feature = "proof-composition")]
 use tokio::{task, try_join};
+use tracing::error;
 #[cfg(feature = "proof-composition")]
 use twap_error_bound_floating::calculate_twap;
 
+pub mod hashing_input;
+pub mod host_cache;
+pub mod journal;
+pub mod simple_mock;
+
+#[cfg(feature = "proof-composition")]
+use hashing_input::build_hashing_felts;
+use host_cache::HostComputationCache;
+#[cfg(feature = "proof-composition")]
+use host_cache::reserve_price_key;
+#[cfg(feature = "proof-composition")]
+use journal::ProvenValues;
+
+/// Renders a guest image id as hex, in the form risc0 tooling and verifiers print it.
+pub fn image_id_hex(image_id: [u32; 8]) -> String {
+    risc0_zkvm::sha::Digest::from(image_id).to_string()
+}
+
+/// Encodes a receipt for storage: its journal, prefixed with its length as a little-endian u32,
+/// followed by its Groth16 seal. Receipts of other kinds, such as fake ones, have no seal.
+pub fn encode_receipt(receipt: &Receipt) -> Vec<u8> {
+    let journal = &receipt.journal.bytes;
+    let seal = receipt
+        .inner
+        .groth16()
+        .map(|groth16| groth16.seal.as_slice())
+        .unwrap_or_default();
+
+    let mut bytes = Vec::with_capacity(4 + journal.len() + seal.len());
+    bytes.extend_from_slice(&(journal.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(journal);
+    bytes.extend_from_slice(seal);
+    bytes
+}
+
+/// A receipt as stored by [`encode_receipt`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredReceipt {
+    pub journal: Vec<u8>,
+    pub seal: Vec<u8>,
+}
+
+/// Decodes a receipt encoded by [`encode_receipt`], or returns `None` if the bytes are too short
+/// for the journal length they declare.
+pub fn decode_receipt(bytes: &[u8]) -> Option<StoredReceipt> {
+    let (len, rest) = bytes.split_first_chunk::<4>()?;
+    let journal_len = u32::from_le_bytes(*len) as usize;
+    if rest.len() < journal_len {
+        return None;
+    }
+    let (journal, seal) = rest.split_at(journal_len);
+
+    Some(StoredReceipt {
+        journal: journal.to_vec(),
+        seal: seal.to_vec(),
+    })
+}
+
 #[async_trait::async_trait]
 pub trait ProofProvider {
     // TODO: separate composition from generation
-    // TODO: add error handling
 
     async fn generate_proofs_from_data(
         &self,
         start_timestamp: i64,
         end_timestamp: i64,
         raw_input: Vec<String>,
-    ) -> Result<Receipt>;
+    ) -> Result<Receipt, ProofError>;
+
+    /// Image id of the guest whose receipts this provider produces, needed to verify the
+    /// proof on-chain. `None` when the provider doesn't run a real guest.
+    fn image_id(&self) -> Option<[u32; 8]> {
+        None
+    }
+
+    /// Rejects a time range too wide to prove, before any data is fetched for it. The default
+    /// accepts any range.
+    fn check_span(&self, _start_timestamp: i64, _end_timestamp: i64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks a generated receipt before it is emitted. The default accepts any receipt, for
+    /// providers whose receipts carry no journal.
+    fn validate_receipt(&self, _receipt: &Receipt) -> Result<()> {
+        Ok(())
+    }
+
+    /// Generates the proof along with how each metric's sub-proof in the composite came out.
+    /// The default reports no metric status, for providers that don't compose sub-proofs.
+    async fn generate_proof_with_metric_status(
+        &self,
+        start_timestamp: i64,
+        end_timestamp: i64,
+        raw_input: Vec<String>,
+    ) -> Result<(Receipt, Option<MetricStatus>), ProofError> {
+        let receipt = self
+            .generate_proofs_from_data(start_timestamp, end_timestamp, raw_input)
+            .await?;
+        Ok((receipt, None))
+    }
+
+    /// Checks that whatever the provider proves with can be reached. The default assumes it
+    /// can, for providers proving in process.
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Estimates the cost of proving `ranges` without proving them. The default fails with
+    /// [`EstimateUnsupported`], for providers whose cost can't be derived from the ranges.
+    fn estimate(&self, _ranges: &ProofTimestampRanges) -> Result<ProofEstimate> {
+        Err(EstimateUnsupported.into())
+    }
+
+    /// What the provider can do, for operators to tell. The default claims nothing.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+}
+
+/// What a [`ProofProvider`] can do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Generates real proofs, as opposed to failing or returning fake receipts.
+    pub can_prove: bool,
+    /// Proves each metric on its own instead of composing them into a single proof.
+    pub supports_separate: bool,
+    /// Generates Groth16 receipts of a known guest, which can be verified on-chain.
+    pub supports_onchain_calldata: bool,
+}
+
+/// Rough cost of proving a set of ranges, as estimated by [`ProofProvider::estimate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEstimate {
+    /// Hourly fee points fetched for the ranges.
+    pub fee_points: usize,
+    /// Periods the reserve price simulation runs over.
+    pub n_periods: usize,
+}
+
+/// The provider can't estimate the cost of a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EstimateUnsupported;
+
+impl std::fmt::Display for EstimateUnsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The proof provider doesn't support estimates")
+    }
+}
+
+impl std::error::Error for EstimateUnsupported {}
+
+/// How long a health check waits for a connection before deeming the endpoint unreachable.
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks that a TCP connection can be opened to the host of `url`.
+pub async fn check_reachable(url: &str, timeout: Duration) -> Result<()> {
+    let parsed = Url::parse(url).map_err(|e| eyre!("Invalid URL {}: {}", url, e))?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| eyre!("URL {} has no host", url))?;
+    let port = parsed
+        .port_or_known_default()
+        .ok_or_else(|| eyre!("URL {} has no port", url))?;
+
+    match tokio::time::timeout(timeout, TcpStream::connect((host, port))).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(eyre!("Failed to reach {}: {}", url, e)),
+        Err(_) => Err(eyre!("Timed out reaching {} after {:?}", url, timeout)),
+    }
+}
+
+/// Runs the provider's health check before the service starts taking jobs. When it fails,
+/// startup fails unless `allow_degraded` is set, in which case the service keeps running and
+/// jobs are retried until the provider is reachable again.
+pub async fn check_provider_health<P: ProofProvider + Sync + ?Sized>(
+    provider: &P,
+    allow_degraded: bool,
+) -> Result<()> {
+    let Err(e) = provider.health_check().await else {
+        return Ok(());
+    };
+
+    if !allow_degraded {
+        return Err(eyre!("Proof provider health check failed: {}", e));
+    }
+
+    error!("==================================================================");
+    error!("Proof provider health check failed: {}", e);
+    error!("Starting in degraded mode, jobs will fail until it is reachable");
+    error!("==================================================================");
+    Ok(())
+}
+
+/// A generated receipt that failed verification against the guest's image id. Proving the same
+/// input again yields the same receipt, so unlike other proving errors it isn't worth retrying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationError(pub String);
+
+impl std::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to verify proof: {}", self.0)
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Why proof generation failed, telling errors worth retrying apart from ones that fail again
+/// on the same input.
+#[derive(Debug)]
+pub enum ProofError {
+    /// May succeed on retry, such as Bonsai being unreachable or the prover failing.
+    Transient(eyre::Report),
+    /// Fails again on retry, such as invalid input or a receipt failing verification.
+    Permanent(eyre::Report),
+}
+
+impl ProofError {
+    pub fn transient(error: impl Into<eyre::Report>) -> Self {
+        Self::Transient(error.into())
+    }
+
+    pub fn permanent(error: impl Into<eyre::Report>) -> Self {
+        Self::Permanent(error.into())
+    }
+
+    pub const fn is_permanent(&self) -> bool {
+        matches!(self, Self::Permanent(_))
+    }
+
+    /// Whether the error is a receipt failing verification.
+    pub fn is_verification_failure(&self) -> bool {
+        self.report().downcast_ref::<VerificationError>().is_some()
+    }
+
+    fn report(&self) -> &eyre::Report {
+        match self {
+            Self::Transient(report) | Self::Permanent(report) => report,
+        }
+    }
+}
+
+impl std::fmt::Display for ProofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.report())
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+/// Errors that aren't classified are assumed to be transient, so the job is retried.
+impl From<eyre::Report> for ProofError {
+    fn from(error: eyre::Report) -> Self {
+        Self::Transient(error)
+    }
+}
+
+impl From<VerificationError> for ProofError {
+    fn from(error: VerificationError) -> Self {
+        Self::permanent(error)
+    }
+}
+
+/// How the sub-proof of a single metric was verified.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricOutcome {
+    /// Whether the sub-proof verified. A composite proof is only produced when all of them do.
+    pub verified: bool,
+    /// Error tolerance, in percent, the metric was verified against. `None` for metrics that
+    /// are proven exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+    /// Value the sub-proof proved for the metric.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+}
+
+impl MetricOutcome {
+    pub const fn verified(tolerance: Option<f64>) -> Self {
+        Self {
+            verified: true,
+            tolerance,
+            value: None,
+        }
+    }
+
+    pub const fn with_value(mut self, value: f64) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Outcome of a metric the composite proved as `proven`, from the sub-proofs it was composed
+    /// of: verified only when every sub-proof verified and `proven` is within `tolerance`
+    /// percent of the value of each sub-proof carrying one, or equal to it for metrics proven
+    /// exactly.
+    pub fn from_sub_proofs(sub_proofs: &[SubProof], tolerance: Option<f64>, proven: f64) -> Self {
+        let within_tolerance = |value: f64| match tolerance {
+            Some(tolerance) => (proven - value).abs() <= value.abs() * tolerance / 100.0,
+            None => proven == value,
+        };
+        let verified = sub_proofs
+            .iter()
+            .all(|sub_proof| sub_proof.verified && sub_proof.value.is_none_or(within_tolerance));
+
+        Self {
+            verified,
+            tolerance,
+            value: Some(proven),
+        }
+    }
+}
+
+/// How a single sub-proof of a metric came out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubProof {
+    /// Whether its receipt verified.
+    pub verified: bool,
+    /// Value of the metric it proved, for sub-proofs that prove one rather than an intermediate
+    /// step.
+    pub value: Option<f64>,
+}
+
+/// Per-metric outcome of the sub-proofs making up a composite proof, so a metric proven at a
+/// looser tolerance can be told apart from the others.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricStatus {
+    pub twap: MetricOutcome,
+    pub reserve_price: MetricOutcome,
+    pub max_return: MetricOutcome,
+}
+
+/// Largest span (in hours) a single proof request may cover. Anything wider is treated as a
+/// malformed request, as fetching the fee data for it could exhaust memory.
+pub const DEFAULT_MAX_SPAN_HOURS: u64 = 5760;
+
+/// Rejects time ranges that are reversed or wider than `max_span_hours`.
+pub fn check_max_span(start_timestamp: i64, end_timestamp: i64, max_span_hours: u64) -> Result<()> {
+    if end_timestamp < start_timestamp {
+        return Err(eyre!(
+            "Invalid time range: start {} is after end {}",
+            start_timestamp,
+            end_timestamp
+        ));
+    }
+
+    let span_hours = end_timestamp.abs_diff(start_timestamp).div_ceil(HOUR_SECS);
+    if span_hours > max_span_hours {
+        return Err(eyre!(
+            "Time range spans {} hours, exceeding the maximum of {} hours",
+            span_hours,
+            max_span_hours
+        ));
+    }
+
+    Ok(())
+}
+
+/// Number of felts the hashing guest takes, i.e. 8 months of hourly fees.
+pub const HASHING_INPUT_LEN: usize = 5760;
+
+/// Number of trailing hourly points the reserve price is computed from, i.e. 3 months.
+pub const RESERVE_PRICE_HOURS: usize = 2160;
+
+/// Periods the reserve price simulation of a composite proof runs over.
+pub const RESERVE_PRICE_N_PERIODS: usize = 720;
+
+/// Paths the reserve price simulation of a composite proof runs.
+pub const RESERVE_PRICE_SIMULATION_PATHS: u64 = 4000;
+
+/// Hourly data a composite proof is built from: `total_hours` points are hashed, of which the
+/// trailing `reserve_hours` feed the reserve price, TWAP and max return computations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataWindows {
+    pub total_hours: usize,
+    pub reserve_hours: usize,
+}
+
+impl DataWindows {
+    pub const DEFAULT: Self = Self {
+        total_hours: HASHING_INPUT_LEN,
+        reserve_hours: RESERVE_PRICE_HOURS,
+    };
+
+    /// Rejects empty windows and a reserve window wider than the hashed data.
+    pub fn validate(&self) -> Result<()> {
+        if self.total_hours == 0 || self.reserve_hours == 0 {
+            return Err(eyre!(
+                "Invalid data windows {:?}: both must be positive",
+                self
+            ));
+        }
+        if self.reserve_hours > self.total_hours {
+            return Err(eyre!(
+                "Invalid data windows: {} reserve hours exceed the {} hashed hours",
+                self.reserve_hours,
+                self.total_hours
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Range of the trailing reserve window in `len` hourly points. Shorter data is used whole.
+    pub const fn reserve_range(&self, len: usize) -> std::ops::Range<usize> {
+        len.saturating_sub(self.reserve_hours)..len
+    }
+}
+
+impl Default for DataWindows {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Error tolerances the guests verify the host-side computations against. Deserializing
+/// rejects tolerances [`Self::validate`] would, so a request carrying them fails to parse
+/// instead of failing proof setup. Missing tolerances take their default.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Tolerances {
+    /// Allowed TWAP error, in percent.
+    #[serde(deserialize_with = "deserialize_tolerance")]
+    pub twap: f64,
+    /// Allowed reserve price error, in percent.
+    #[serde(deserialize_with = "deserialize_tolerance")]
+    pub reserve_price: f64,
+}
+
+/// Deserializes a tolerance, rejecting values that aren't finite and positive. JSON has no
+/// NaN or infinity, so those only come as strings, which aren't numbers either.
+pub fn deserialize_tolerance<'de, D>(deserializer: D) -> std::result::Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = f64::deserialize(deserializer)?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(serde::de::Error::custom(format!(
+            "invalid tolerance {}: must be finite and positive",
+            value
+        )));
+    }
+    Ok(value)
+}
+
+impl Tolerances {
+    pub const DEFAULT: Self = Self {
+        twap: 1.0,
+        reserve_price: 5.0,
+    };
+
+    /// Rejects tolerances the error-bound guests can't work with: zero, negative or non-finite.
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [("twap", self.twap), ("reserve price", self.reserve_price)] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(eyre!(
+                    "Invalid {} tolerance {}: must be finite and positive",
+                    name,
+                    value
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Bonsai endpoint and credentials for a provider, instead of the `BONSAI_API_URL` and
+/// `BONSAI_API_KEY` environment variables. The risc0 prover only reads Bonsai settings from the
+/// environment, so the config has to be [installed](Self::install) at startup before proving.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BonsaiConfig {
+    pub api_url: String,
+    pub api_key: String,
+}
+
+impl std::fmt::Debug for BonsaiConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BonsaiConfig")
+            .field("api_url", &self.api_url)
+            .field("api_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Config exported to the environment by [`BonsaiConfig::install`].
+static INSTALLED_BONSAI_CONFIG: OnceLock<BonsaiConfig> = OnceLock::new();
+
+impl BonsaiConfig {
+    /// Exports the config as `BONSAI_API_URL` and `BONSAI_API_KEY`. The environment is global to
+    /// the process, so only one config can be installed: installing it again does nothing and
+    /// installing a different one fails.
+    ///
+    /// # Safety
+    ///
+    /// Must be called before any other thread is started, in particular before the tokio runtime
+    /// is built, as writing the environment while another thread reads it is undefined behavior.
+    pub unsafe fn install(&self) -> Result<()> {
+        if self.claim(&INSTALLED_BONSAI_CONFIG)? {
+            // SAFETY: the caller guarantees no other thread is running yet.
+            unsafe {
+                std::env::set_var("BONSAI_API_URL", &self.api_url);
+                std::env::set_var("BONSAI_API_KEY", &self.api_key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether this is the config installed in the environment, i.e. the one the prover uses.
+    pub fn is_installed(&self) -> bool {
+        INSTALLED_BONSAI_CONFIG.get() == Some(self)
+    }
+
+    /// Records the config in `slot`, returning whether it is the first one recorded. Fails when
+    /// a different config was recorded before.
+    fn claim(&self, slot: &OnceLock<Self>) -> Result<bool> {
+        let mut claimed = false;
+        let recorded = slot.get_or_init(|| {
+            claimed = true;
+            self.clone()
+        });
+        if recorded != self {
+            return Err(eyre!(
+                "A different Bonsai config is already installed: {:?}",
+                recorded
+            ));
+        }
+
+        Ok(claimed)
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct BonsaiProofProvider;
+pub struct BonsaiProofProvider {
+    max_span_hours: u64,
+    tolerances: Tolerances,
+    data_windows: DataWindows,
+    bonsai_config: Option<BonsaiConfig>,
+    reserve_price_cache: Option<Arc<HostComputationCache>>,
+}
 
 impl BonsaiProofProvider {
     pub const fn new() -> Self {
-        Self
+        Self::with_max_span_hours(DEFAULT_MAX_SPAN_HOURS)
+    }
+
+    pub const fn with_max_span_hours(max_span_hours: u64) -> Self {
+        Self {
+            max_span_hours,
+            tolerances: Tolerances::DEFAULT,
+            data_windows: DataWindows::DEFAULT,
+            bonsai_config: None,
+            reserve_price_cache: None,
+        }
+    }
+
+    pub const fn with_data_windows(mut self, data_windows: DataWindows) -> Self {
+        self.data_windows = data_windows;
+        self
+    }
+
+    /// Proves with the given Bonsai endpoint and credentials instead of those in the
+    /// environment. Proving fails unless the config was [installed](BonsaiConfig::install) at
+    /// startup, so all providers of a process share the same one.
+    pub fn with_config(mut self, bonsai_config: BonsaiConfig) -> Self {
+        self.bonsai_config = Some(bonsai_config);
+        self
+    }
+
+    /// Bonsai settings set with [`Self::with_config`], `None` when they come from the
+    /// environment.
+    pub const fn bonsai_config(&self) -> Option<&BonsaiConfig> {
+        self.bonsai_config.as_ref()
+    }
+
+    /// Reuses the reserve price computed on the host for identical data instead of computing it
+    /// again, going straight to the error bound proofs. The cache may be shared by providers.
+    pub fn with_reserve_price_cache(mut self, cache: Arc<HostComputationCache>) -> Self {
+        self.reserve_price_cache = Some(cache);
+        self
+    }
+
+    pub const fn with_tolerances(mut self, tolerances: Tolerances) -> Self {
+        self.tolerances = tolerances;
+        self
     }
 }
 
@@ -75,18 +650,45 @@ impl ProofProvider for BonsaiProofProvider {
         start_timestamp: i64,
         end_timestamp: i64,
         raw_input: Vec<String>,
-    ) -> Result<Receipt> {
-        // hashing inputs
-        let mut res = Vec::with_capacity(5760);
-        for i in 0..5760 {
-            let index = i % raw_input.len();
-            let felt = Felt::from_hex_unchecked(&raw_input[index]);
-            res.push(felt);
+    ) -> Result<Receipt, ProofError> {
+        let (receipt, _) = self
+            .generate_proof_with_metric_status(start_timestamp, end_timestamp, raw_input)
+            .await?;
+        Ok(receipt)
+    }
+
+    /// Reports each metric as verified when all of its sub-proof receipts verify and the value
+    /// the composite proved for it agrees with theirs, along with that value.
+    #[cfg(feature = "proof-composition")]
+    async fn generate_proof_with_metric_status(
+        &self,
+        start_timestamp: i64,
+        end_timestamp: i64,
+        raw_input: Vec<String>,
+    ) -> Result<(Receipt, Option<MetricStatus>), ProofError> {
+        // Invalid input or configuration won't get better on retry
+        self.check_span(start_timestamp, end_timestamp)
+            .map_err(ProofError::permanent)?;
+        self.tolerances.validate().map_err(ProofError::permanent)?;
+        self.data_windows
+            .validate()
+            .map_err(ProofError::permanent)?;
+        if let Some(bonsai_config) = self.bonsai_config.as_ref()
+            && !bonsai_config.is_installed()
+        {
+            return Err(ProofError::permanent(eyre!(
+                "Bonsai config {:?} must be installed at startup before proving",
+                bonsai_config
+            )));
         }
+
+        // hashing inputs
+        let res = build_hashing_felts(&raw_input, self.data_windows.total_hours);
         let (hashing_receipt, hashing_res) = hash_felts(HashingFeltInput { inputs: res });
 
         let data_8_months = hashing_res.f64_inputs;
-        let data = data_8_months[data_8_months.len().saturating_sub(2160)..].to_vec();
+        // Keep the trailing 3 months of hourly data
+        let data = data_8_months[self.data_windows.reserve_range(data_8_months.len())].to_vec();
 
         // max return
         let input = MaxReturnInput { data: data.clone() };
@@ -98,7 +700,7 @@ impl ProofProvider for BonsaiProofProvider {
         let twap_original = floating_point::calculate_twap(&data);
         let input = TwapErrorBoundInput {
             avg_hourly_gas_fee: data.clone(),
-            twap_tolerance: 1.0,
+            twap_tolerance: self.tolerances.twap,
             twap_result: twap_original,
         };
 
@@ -107,15 +709,26 @@ impl ProofProvider for BonsaiProofProvider {
         // reserve price
         // run rust code in host
         // ensure convergence in host
-        let n_periods = 720;
+        let n_periods = RESERVE_PRICE_N_PERIODS as _;
 
-        let data_with_timestamps = convert_data_to_vec_of_tuples(data.clone(), start_timestamp);
-        let res = original::calculate_reserve_price(&data_with_timestamps, 15000, n_periods);
+        // The reserve price dominates the host computation, so reuse one computed before for
+        // identical data
+        let compute_reserve_price = || {
+            let data_with_timestamps = convert_data_to_vec_of_tuples(data.clone(), start_timestamp);
+            original::calculate_reserve_price(&data_with_timestamps, 15000, n_periods)
+        };
+        let res = match &self.reserve_price_cache {
+            Some(cache) => cache.get_or_compute(
+                reserve_price_key(&data, start_timestamp),
+                compute_reserve_price,
+            ),
+            None => Arc::new(compute_reserve_price()),
+        };
 
-        let num_paths = 4000;
+        let num_paths = RESERVE_PRICE_SIMULATION_PATHS as _;
         let gradient_tolerance = 5e-2;
         let floating_point_tolerance = 0.00001; // 0.00001%
-        let reserve_price_tolerance = 5.0; // 5%
+        let reserve_price_tolerance = self.tolerances.reserve_price;
 
         // Making all these async via tokio spawns
 
@@ -203,7 +816,7 @@ impl ProofProvider for BonsaiProofProvider {
                 slope,
                 intercept,
                 reserve_price,
-                tolerance: reserve_price_tolerance, // 5%
+                tolerance: reserve_price_tolerance,
             });
 
             receipt
@@ -216,24 +829,24 @@ impl ProofProvider for BonsaiProofProvider {
             data_8_months,
             start_timestamp,
             end_timestamp,
-            positions: res.positions,
-            pt: convert_array1_to_dvec(res.pt),
-            pt_1: convert_array1_to_dvec(res.pt_1),
+            positions: res.positions.clone(),
+            pt: convert_array1_to_dvec(res.pt.clone()),
+            pt_1: convert_array1_to_dvec(res.pt_1.clone()),
             gradient_tolerance,
             de_seasonalised_detrended_log_base_fee: convert_array1_to_dvec(
-                res.de_seasonalised_detrended_log_base_fee,
+                res.de_seasonalised_detrended_log_base_fee.clone(),
             ),
             n_periods,
             num_paths,
-            season_param: convert_array1_to_dvec(res.season_param),
-            twap_7d: res.twap_7d,
+            season_param: convert_array1_to_dvec(res.season_param.clone()),
+            twap_7d: res.twap_7d.clone(),
             slope: res.slope,
             intercept: res.intercept,
             reserve_price: res.reserve_price,
             floating_point_tolerance,
             reserve_price_tolerance,
             twap_result: twap_original,
-            twap_tolerance: 1.0,
+            twap_tolerance: self.tolerances.twap,
             max_return: max_return_res.1,
         };
 
@@ -248,10 +861,27 @@ impl ProofProvider for BonsaiProofProvider {
         let result_receipt = match result_receipt {
             Ok(receipts) => receipts,
             Err(e) => {
-                return Err(eyre!("Failed to join tasks: {}", e));
+                return Err(eyre!("Failed to join tasks: {}", e).into());
             }
         };
 
+        // The sub-proofs are moved into the composite, so check them before
+        let verifier_context = VerifierContext::default();
+        let sub_proof = |receipt: &Receipt, value: Option<f64>| SubProof {
+            verified: receipt
+                .verify_integrity_with_context(&verifier_context)
+                .is_ok(),
+            value,
+        };
+        let twap_sub_proofs = [sub_proof(&calculate_twap_receipt, Some(twap_original))];
+        let reserve_price_sub_proofs = [
+            sub_proof(&result_receipt.0, None),
+            sub_proof(&result_receipt.1, None),
+            sub_proof(&result_receipt.2, None),
+            sub_proof(&result_receipt.3, Some(res.reserve_price)),
+        ];
+        let max_return_sub_proofs = [sub_proof(&max_return_receipt, Some(max_return_res.1))];
+
         // Composite proof generation
         let env = ExecutorEnv::builder()
             .add_assumption(hashing_receipt)
@@ -279,9 +909,29 @@ impl ProofProvider for BonsaiProofProvider {
         let receipt = prove_info.receipt;
         receipt
             .verify(PROOF_COMPOSITION_TWAP_MAXRETURN_RESERVEPRICE_FLOATING_HASHING_GUEST_ID)
-            .map_err(|e| eyre!("Failed to verify proof: {}", e))?;
+            .map_err(|e| VerificationError(e.to_string()))?;
 
-        Ok(receipt)
+        // Check the values the composite proved against those of its sub-proofs
+        let proven = ProvenValues::decode(&receipt).map_err(ProofError::permanent)?;
+        let metric_status = MetricStatus {
+            twap: MetricOutcome::from_sub_proofs(
+                &twap_sub_proofs,
+                Some(self.tolerances.twap),
+                proven.twap,
+            ),
+            reserve_price: MetricOutcome::from_sub_proofs(
+                &reserve_price_sub_proofs,
+                Some(reserve_price_tolerance),
+                proven.reserve_price,
+            ),
+            max_return: MetricOutcome::from_sub_proofs(
+                &max_return_sub_proofs,
+                None,
+                proven.max_return,
+            ),
+        };
+
+        Ok((receipt, Some(metric_status)))
     }
 
     #[cfg(not(feature = "proof-composition"))]
@@ -290,9 +940,505 @@ impl ProofProvider for BonsaiProofProvider {
         _start_timestamp: i64,
         _end_timestamp: i64,
         _raw_input: Vec<String>,
-    ) -> Result<Receipt> {
-        Err(eyre!(
+    ) -> Result<Receipt, ProofError> {
+        Err(ProofError::permanent(eyre!(
             "Proof composition is disabled. Enable the 'proof-composition' feature to use this functionality."
-        ))
+        )))
+    }
+
+    /// Rejects time ranges that are reversed or wider than the configured maximum span.
+    fn check_span(&self, start_timestamp: i64, end_timestamp: i64) -> Result<()> {
+        check_max_span(start_timestamp, end_timestamp, self.max_span_hours)
+    }
+
+    #[cfg(feature = "proof-composition")]
+    fn image_id(&self) -> Option<[u32; 8]> {
+        Some(PROOF_COMPOSITION_TWAP_MAXRETURN_RESERVEPRICE_FLOATING_HASHING_GUEST_ID)
+    }
+
+    /// Checks that the Bonsai API can be reached. Without a Bonsai endpoint, proofs are
+    /// generated locally and there is nothing to reach.
+    async fn health_check(&self) -> Result<()> {
+        let api_url = match &self.bonsai_config {
+            Some(bonsai_config) => Some(bonsai_config.api_url.clone()),
+            None => std::env::var("BONSAI_API_URL").ok(),
+        };
+        match api_url {
+            Some(api_url) => check_reachable(&api_url, HEALTH_CHECK_TIMEOUT).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Composite Groth16 proofs of the composition guest, only when built with the
+    /// `proof-composition` feature.
+    fn capabilities(&self) -> ProviderCapabilities {
+        let proves = cfg!(feature = "proof-composition");
+        ProviderCapabilities {
+            can_prove: proves,
+            supports_separate: false,
+            supports_onchain_calldata: proves,
+        }
+    }
+
+    /// Sizes the proof from the ranges and the data windows alone, rejecting what proving
+    /// would reject, without invoking the prover.
+    fn estimate(&self, ranges: &ProofTimestampRanges) -> Result<ProofEstimate> {
+        let (start, end) = ranges.overall();
+        self.check_span(
+            i64::try_from(start).map_err(|e| eyre!("Invalid start timestamp {}: {}", start, e))?,
+            i64::try_from(end).map_err(|e| eyre!("Invalid end timestamp {}: {}", end, e))?,
+        )?;
+        self.data_windows.validate()?;
+
+        Ok(ProofEstimate {
+            fee_points: expected_fee_points(start, end, HOUR_SECS),
+            n_periods: RESERVE_PRICE_N_PERIODS,
+        })
+    }
+
+    #[cfg(feature = "proof-composition")]
+    fn validate_receipt(&self, receipt: &Receipt) -> Result<()> {
+        ProvenValues::decode(receipt)?.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn sub_proof(verified: bool, value: Option<f64>) -> SubProof {
+        SubProof { verified, value }
+    }
+
+    #[test]
+    fn test_metric_outcome_verified_when_sub_proofs_agree() {
+        let outcome = MetricOutcome::from_sub_proofs(
+            &[sub_proof(true, None), sub_proof(true, Some(100.0))],
+            Some(1.0),
+            100.5,
+        );
+        assert_eq!(
+            outcome,
+            MetricOutcome::verified(Some(1.0)).with_value(100.5)
+        );
+
+        let exact = MetricOutcome::from_sub_proofs(&[sub_proof(true, Some(0.3))], None, 0.3);
+        assert!(exact.verified);
+    }
+
+    #[test]
+    fn test_failing_sub_proof_marks_only_its_metric_unverified() {
+        let status = MetricStatus {
+            twap: MetricOutcome::from_sub_proofs(&[sub_proof(true, Some(25.0))], Some(1.0), 25.0),
+            reserve_price: MetricOutcome::from_sub_proofs(
+                &[
+                    sub_proof(true, None),
+                    sub_proof(false, None),
+                    sub_proof(true, None),
+                    sub_proof(true, Some(40.0)),
+                ],
+                Some(7.5),
+                40.0,
+            ),
+            max_return: MetricOutcome::from_sub_proofs(&[sub_proof(true, Some(0.3))], None, 0.3),
+        };
+
+        assert!(status.twap.verified);
+        assert!(!status.reserve_price.verified);
+        assert_eq!(status.reserve_price.value, Some(40.0));
+        assert!(status.max_return.verified);
+    }
+
+    #[test]
+    fn test_metric_outcome_unverified_outside_tolerance() {
+        // 2% off a sub-proof value with a 1% tolerance
+        let outcome =
+            MetricOutcome::from_sub_proofs(&[sub_proof(true, Some(100.0))], Some(1.0), 102.0);
+        assert!(!outcome.verified);
+
+        // Metrics proven exactly must match
+        let exact = MetricOutcome::from_sub_proofs(&[sub_proof(true, Some(0.3))], None, 0.31);
+        assert!(!exact.verified);
+    }
+
+    #[test]
+    fn test_check_span_accepts_normal_range() {
+        let provider = BonsaiProofProvider::new();
+
+        // 3 months of hourly data
+        let start = 1_700_000_000;
+        let end = start + 2160 * 3600;
+        assert!(provider.check_span(start, end).is_ok());
+    }
+
+    #[test]
+    fn test_check_span_accepts_max_range() {
+        let provider = BonsaiProofProvider::with_max_span_hours(10);
+        assert!(provider.check_span(0, 10 * 3600).is_ok());
+    }
+
+    #[test]
+    fn test_check_span_rejects_oversized_range() {
+        let provider = BonsaiProofProvider::new();
+
+        // Roughly 3 years
+        let start = 1_600_000_000;
+        let end = start + 3 * 365 * 24 * 3600;
+        let err = provider.check_span(start, end).unwrap_err();
+        assert!(err.to_string().contains("exceeding the maximum"));
+    }
+
+    #[test]
+    fn test_check_span_rejects_reversed_range() {
+        let provider = BonsaiProofProvider::new();
+        assert!(provider.check_span(2000, 1000).is_err());
+    }
+
+    #[test]
+    fn test_default_tolerances_are_valid() {
+        assert!(Tolerances::default().validate().is_ok());
+        assert!(
+            Tolerances {
+                twap: 0.5,
+                reserve_price: 10.0,
+            }
+            .validate()
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_zero_tolerance_is_rejected() {
+        let tolerances = Tolerances {
+            twap: 0.0,
+            ..Tolerances::DEFAULT
+        };
+        let err = tolerances.validate().unwrap_err();
+        assert!(err.to_string().contains("twap tolerance"));
+    }
+
+    #[test]
+    fn test_negative_tolerance_is_rejected() {
+        let tolerances = Tolerances {
+            reserve_price: -5.0,
+            ..Tolerances::DEFAULT
+        };
+        let err = tolerances.validate().unwrap_err();
+        assert!(err.to_string().contains("reserve price tolerance"));
+    }
+
+    #[test]
+    fn test_non_finite_tolerance_is_rejected() {
+        for value in [f64::NAN, f64::INFINITY] {
+            let tolerances = Tolerances {
+                twap: value,
+                ..Tolerances::DEFAULT
+            };
+            assert!(tolerances.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_valid_tolerances_deserialize() {
+        let tolerances: Tolerances =
+            serde_json::from_str(r#"{"twap": 0.5, "reserve_price": 10}"#).unwrap();
+        assert_eq!(
+            tolerances,
+            Tolerances {
+                twap: 0.5,
+                reserve_price: 10.0,
+            }
+        );
+
+        let tolerances: Tolerances = serde_json::from_str(r#"{"twap": 2.0}"#).unwrap();
+        assert_eq!(tolerances.reserve_price, Tolerances::DEFAULT.reserve_price);
+    }
+
+    #[test]
+    fn test_nan_tolerance_fails_to_deserialize() {
+        assert!(serde_json::from_str::<Tolerances>(r#"{"twap": "NaN"}"#).is_err());
+        assert!(serde_json::from_str::<Tolerances>(r#"{"twap": null}"#).is_err());
+    }
+
+    #[test]
+    fn test_negative_tolerance_fails_to_deserialize() {
+        let err = serde_json::from_str::<Tolerances>(r#"{"reserve_price": -5.0}"#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("invalid tolerance -5: must be finite and positive")
+        );
+        assert!(serde_json::from_str::<Tolerances>(r#"{"twap": 0}"#).is_err());
+    }
+
+    fn estimate_ranges(start: u64, end: u64) -> ProofTimestampRanges {
+        ProofTimestampRanges {
+            twap: (start, end),
+            reserve_price: (start, end),
+            max_return: (start, end),
+        }
+    }
+
+    #[test]
+    fn test_estimate_scales_with_range_width() {
+        let provider = BonsaiProofProvider::new();
+
+        let narrow = provider
+            .estimate(&estimate_ranges(0, 720 * HOUR_SECS))
+            .unwrap();
+        let wide = provider
+            .estimate(&estimate_ranges(0, 2160 * HOUR_SECS))
+            .unwrap();
+
+        assert_eq!(narrow.fee_points, 721);
+        assert_eq!(wide.fee_points, 2161);
+        assert!(narrow.fee_points < wide.fee_points);
+        // The simulation runs over the same periods whatever the span
+        assert_eq!(narrow.n_periods, RESERVE_PRICE_N_PERIODS);
+        assert_eq!(wide.n_periods, RESERVE_PRICE_N_PERIODS);
+    }
+
+    #[test]
+    fn test_estimate_rejects_span_proving_would_reject() {
+        let provider = BonsaiProofProvider::with_max_span_hours(24);
+        let err = provider
+            .estimate(&estimate_ranges(0, 48 * HOUR_SECS))
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeding the maximum"));
+    }
+
+    #[test]
+    fn test_bonsai_capabilities() {
+        let capabilities = BonsaiProofProvider::new().capabilities();
+
+        assert_eq!(capabilities.can_prove, cfg!(feature = "proof-composition"));
+        assert_eq!(
+            capabilities.supports_onchain_calldata,
+            BonsaiProofProvider::new().image_id().is_some()
+        );
+        assert!(!capabilities.supports_separate);
+    }
+
+    #[test]
+    fn test_capabilities_default_to_none() {
+        assert_eq!(
+            UnreachableProofProvider.capabilities(),
+            ProviderCapabilities {
+                can_prove: false,
+                supports_separate: false,
+                supports_onchain_calldata: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_estimate_is_unsupported_by_default() {
+        let err = UnreachableProofProvider
+            .estimate(&estimate_ranges(0, HOUR_SECS))
+            .unwrap_err();
+        assert!(err.downcast_ref::<EstimateUnsupported>().is_some());
+    }
+
+    #[test]
+    fn test_default_data_windows_are_valid() {
+        assert_eq!(DataWindows::default().validate().ok(), Some(()));
+        assert_eq!(DataWindows::DEFAULT.total_hours, 5760);
+        assert_eq!(DataWindows::DEFAULT.reserve_hours, 2160);
+    }
+
+    #[test]
+    fn test_invalid_data_windows_are_rejected() {
+        for data_windows in [
+            DataWindows {
+                total_hours: 0,
+                reserve_hours: 0,
+            },
+            DataWindows {
+                total_hours: 5760,
+                reserve_hours: 0,
+            },
+            DataWindows {
+                total_hours: 2159,
+                reserve_hours: 2160,
+            },
+        ] {
+            assert!(
+                data_windows.validate().is_err(),
+                "{:?} should be rejected",
+                data_windows
+            );
+        }
+        assert!(
+            DataWindows {
+                total_hours: 2160,
+                reserve_hours: 2160,
+            }
+            .validate()
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_reserve_range_keeps_trailing_hours() {
+        let data_windows = DataWindows::DEFAULT;
+        assert_eq!(data_windows.reserve_range(5760), 3600..5760);
+        assert_eq!(data_windows.reserve_range(5760).len(), 2160);
+        // Shorter series are used whole
+        assert_eq!(data_windows.reserve_range(100), 0..100);
+        assert_eq!(data_windows.reserve_range(0), 0..0);
+    }
+
+    #[test]
+    fn test_bonsai_config_is_retained() {
+        let config = BonsaiConfig {
+            api_url: "https://bonsai.example".to_string(),
+            api_key: "secret-key".to_string(),
+        };
+        let provider = BonsaiProofProvider::new().with_config(config.clone());
+
+        assert_eq!(provider.bonsai_config(), Some(&config));
+        assert_eq!(BonsaiProofProvider::new().bonsai_config(), None);
+        // The key must not end up in logs
+        assert!(!format!("{:?}", provider).contains("secret-key"));
+    }
+
+    #[test]
+    fn test_bonsai_config_claimed_once() {
+        let slot = OnceLock::new();
+        let config = bonsai_config("https://bonsai.example".to_string());
+
+        assert!(config.claim(&slot).unwrap());
+        // Installing the same config again is a no-op
+        assert!(!config.clone().claim(&slot).unwrap());
+
+        let err = bonsai_config("https://other.example".to_string())
+            .claim(&slot)
+            .unwrap_err();
+        assert!(err.to_string().contains("already installed"));
+        assert!(!err.to_string().contains("test-key"));
+        assert_eq!(slot.get(), Some(&config));
+    }
+
+    #[test]
+    fn test_bonsai_config_not_installed_until_install() {
+        assert!(!bonsai_config("https://bonsai.example".to_string()).is_installed());
+    }
+
+    #[cfg(feature = "proof-composition")]
+    #[test]
+    fn test_bonsai_provider_reports_image_id() {
+        let provider = BonsaiProofProvider::new();
+        assert_eq!(
+            provider.image_id(),
+            Some(PROOF_COMPOSITION_TWAP_MAXRETURN_RESERVEPRICE_FLOATING_HASHING_GUEST_ID)
+        );
+    }
+
+    #[test]
+    fn test_encode_receipt_prefixes_journal_length() {
+        use risc0_zkvm::{Digest, FakeReceipt, InnerReceipt, MaybePruned};
+
+        let fake_receipt = FakeReceipt::new(MaybePruned::Pruned(Digest::ZERO));
+        let receipt = Receipt::new(InnerReceipt::Fake(fake_receipt), vec![7, 8, 9]);
+
+        assert_eq!(encode_receipt(&receipt), vec![3, 0, 0, 0, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_decode_receipt_splits_journal_and_seal() {
+        assert_eq!(
+            decode_receipt(&[2, 0, 0, 0, 7, 8, 1, 2, 3]),
+            Some(StoredReceipt {
+                journal: vec![7, 8],
+                seal: vec![1, 2, 3],
+            })
+        );
+        assert_eq!(decode_receipt(&[3, 0, 0, 0, 7, 8]), None);
+        assert_eq!(decode_receipt(&[3, 0]), None);
+    }
+
+    #[test]
+    fn test_image_id_hex_uses_little_endian_words() {
+        let hex = image_id_hex([1, 0, 0, 0, 0, 0, 0, 0xdeadbeef]);
+        assert_eq!(hex.len(), 64);
+        assert!(hex.starts_with("01000000"));
+        assert!(hex.ends_with("efbeadde"));
+    }
+
+    #[cfg(not(feature = "proof-composition"))]
+    #[test]
+    fn test_disabled_provider_has_no_image_id() {
+        assert_eq!(BonsaiProofProvider::new().image_id(), None);
+    }
+
+    struct UnreachableProofProvider;
+
+    #[async_trait::async_trait]
+    impl ProofProvider for UnreachableProofProvider {
+        async fn generate_proofs_from_data(
+            &self,
+            _start_timestamp: i64,
+            _end_timestamp: i64,
+            _raw_input: Vec<String>,
+        ) -> Result<Receipt, ProofError> {
+            Err(ProofError::transient(eyre!("Bonsai unreachable")))
+        }
+
+        async fn health_check(&self) -> Result<()> {
+            Err(eyre!("Bonsai unreachable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_health_check_fails_startup() {
+        let err = check_provider_health(&UnreachableProofProvider, false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Proof provider health check failed: Bonsai unreachable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_health_check_allowed_when_degraded() {
+        assert!(
+            check_provider_health(&UnreachableProofProvider, true)
+                .await
+                .is_ok()
+        );
+    }
+
+    fn bonsai_config(api_url: String) -> BonsaiConfig {
+        BonsaiConfig {
+            api_url,
+            api_key: "test-key".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bonsai_health_check_reaches_configured_endpoint() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let provider = BonsaiProofProvider::new().with_config(bonsai_config(api_url));
+        assert!(check_provider_health(&provider, false).await.is_ok());
+
+        // Nothing listens on port 1, so the connection is refused
+        let provider =
+            BonsaiProofProvider::new().with_config(bonsai_config("http://127.0.0.1:1".to_string()));
+        let err = provider.health_check().await.unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Failed to reach http://127.0.0.1:1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_reachable_rejects_invalid_url() {
+        assert!(
+            check_reachable("not a url", HEALTH_CHECK_TIMEOUT)
+                .await
+                .is_err()
+        );
     }
 }
@@ -0,0 +1,127 @@
+use eyre::Result;
+use risc0_zkvm::sha::{Impl, Sha256};
+use risc0_zkvm::{Digest, FakeReceipt, InnerReceipt, MaybePruned, Receipt};
+
+use super::{ProofError, ProofProvider, ProviderCapabilities};
+
+/// Proof provider that returns fake receipts without running the prover.
+/// Useful for tests and for running the pipeline locally without Bonsai.
+#[derive(Debug, Clone, Default)]
+pub struct SimpleMockProofProvider {
+    seeded: bool,
+}
+
+impl SimpleMockProofProvider {
+    /// Every receipt carries a `Digest::ZERO` claim.
+    pub const fn new() -> Self {
+        Self { seeded: false }
+    }
+
+    /// Receipts carry a claim digest derived from the job's time range, so different
+    /// inputs yield different, but deterministic, receipts.
+    pub const fn seeded() -> Self {
+        Self { seeded: true }
+    }
+
+    fn claim_digest(&self, start_timestamp: i64, end_timestamp: i64) -> Digest {
+        if !self.seeded {
+            return Digest::ZERO;
+        }
+
+        let mut seed = Vec::with_capacity(16);
+        seed.extend_from_slice(&start_timestamp.to_le_bytes());
+        seed.extend_from_slice(&end_timestamp.to_le_bytes());
+        *Impl::hash_bytes(&seed)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofProvider for SimpleMockProofProvider {
+    async fn generate_proofs_from_data(
+        &self,
+        start_timestamp: i64,
+        end_timestamp: i64,
+        _raw_input: Vec<String>,
+    ) -> Result<Receipt, ProofError> {
+        let claim_digest = self.claim_digest(start_timestamp, end_timestamp);
+        let fake_receipt = FakeReceipt::new(MaybePruned::Pruned(claim_digest));
+        Ok(Receipt::new(InnerReceipt::Fake(fake_receipt), vec![]))
+    }
+
+    /// Fake receipts prove nothing and can't be verified on-chain.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::sha::Digestible;
+
+    async fn receipt_digest(provider: &SimpleMockProofProvider, start: i64, end: i64) -> Digest {
+        provider
+            .generate_proofs_from_data(start, end, vec![])
+            .await
+            .unwrap()
+            .claim()
+            .unwrap()
+            .digest()
+    }
+
+    #[tokio::test]
+    async fn test_unseeded_provider_returns_zero_digest() {
+        let provider = SimpleMockProofProvider::new();
+
+        assert_eq!(receipt_digest(&provider, 1000, 2000).await, Digest::ZERO);
+        assert_eq!(receipt_digest(&provider, 3000, 4000).await, Digest::ZERO);
+        assert_eq!(provider.image_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_provider_differs_across_ranges() {
+        let provider = SimpleMockProofProvider::seeded();
+
+        let first = receipt_digest(&provider, 1000, 2000).await;
+        let second = receipt_digest(&provider, 1000, 3000).await;
+        assert_ne!(first, second);
+        assert_ne!(first, Digest::ZERO);
+    }
+
+    #[test]
+    fn test_mock_capabilities() {
+        for provider in [
+            SimpleMockProofProvider::new(),
+            SimpleMockProofProvider::seeded(),
+        ] {
+            let capabilities = provider.capabilities();
+            assert!(!capabilities.can_prove);
+            assert!(!capabilities.supports_separate);
+            assert!(!capabilities.supports_onchain_calldata);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_reports_no_metric_status() {
+        let provider = SimpleMockProofProvider::seeded();
+
+        let (receipt, metric_status) = provider
+            .generate_proof_with_metric_status(1000, 2000, vec![])
+            .await
+            .unwrap();
+        assert_eq!(
+            receipt.claim().unwrap().digest(),
+            receipt_digest(&provider, 1000, 2000).await
+        );
+        assert_eq!(metric_status, None);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_provider_is_deterministic() {
+        let provider = SimpleMockProofProvider::seeded();
+
+        let first = receipt_digest(&provider, 1000, 2000).await;
+        let second = receipt_digest(&SimpleMockProofProvider::seeded(), 1000, 2000).await;
+        assert_eq!(first, second);
+    }
+}
@@ -1,20 +1,58 @@
 use super::message_queue::{Queue, QueueError, QueueMessage};
 use async_trait::async_trait;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
-use tracing::warn;
+use tokio::time::Instant;
+use tracing::{debug, warn};
 use uuid::Uuid;
 
+struct LocalMessage {
+    message: QueueMessage,
+    expires_at: Option<Instant>,
+    // Set while a received message is in flight, until it becomes visible again
+    invisible_until: Option<Instant>,
+}
+
+impl LocalMessage {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    fn is_visible(&self, now: Instant) -> bool {
+        self.invisible_until
+            .is_none_or(|invisible_until| now >= invisible_until)
+    }
+}
+
 pub struct LocalMessageQueue {
-    messages: Arc<Mutex<Vec<QueueMessage>>>,
+    messages: Arc<Mutex<Vec<LocalMessage>>>,
+    message_ttl: Option<Duration>,
+    visibility_timeout: Option<Duration>,
 }
 
 impl LocalMessageQueue {
     pub fn new() -> Self {
         Self {
             messages: Arc::new(Mutex::new(Vec::new())),
+            message_ttl: None,
+            visibility_timeout: None,
         }
     }
+
+    /// Drops messages that haven't been deleted within `ttl` of being sent, the way SQS drops
+    /// messages older than the queue's retention period.
+    pub fn with_message_ttl(mut self, ttl: Duration) -> Self {
+        self.message_ttl = Some(ttl);
+        self
+    }
+
+    /// Hides received messages for `visibility_timeout`, the way SQS does, so a message that
+    /// isn't deleted in time is received again. Without it, messages stay visible until deleted.
+    pub fn with_visibility_timeout(mut self, visibility_timeout: Duration) -> Self {
+        self.visibility_timeout = Some(visibility_timeout);
+        self
+    }
 }
 
 impl Default for LocalMessageQueue {
@@ -26,23 +64,53 @@ impl Default for LocalMessageQueue {
 #[async_trait]
 impl Queue for LocalMessageQueue {
     async fn send_message(&self, message: String) -> Result<(), QueueError> {
+        self.send_message_with_id(message).await.map(|_| ())
+    }
+
+    async fn send_message_with_id(&self, message: String) -> Result<Option<String>, QueueError> {
+        let id = Uuid::new_v4().to_string();
         let mut messages = self.messages.lock().await;
-        messages.push(QueueMessage {
-            id: Some(Uuid::new_v4().to_string()),
-            body: message,
+        messages.push(LocalMessage {
+            message: QueueMessage {
+                id: Some(id.clone()),
+                body: message,
+            },
+            expires_at: self.message_ttl.map(|ttl| Instant::now() + ttl),
+            invisible_until: None,
         });
-        Ok(())
+        Ok(Some(id))
     }
 
     async fn receive_messages(&self) -> Result<Vec<QueueMessage>, QueueError> {
-        let messages = self.messages.lock().await;
-        let result = messages.clone();
-        Ok(result)
+        let mut messages = self.messages.lock().await;
+        let now = Instant::now();
+        let count = messages.len();
+        messages.retain(|m| !m.is_expired(now));
+        if messages.len() < count {
+            debug!("Dropped {} expired messages", count - messages.len());
+        }
+
+        let invisible_until = self.visibility_timeout.map(|timeout| now + timeout);
+        Ok(messages
+            .iter_mut()
+            .filter(|m| m.is_visible(now))
+            .map(|m| {
+                if invisible_until.is_some() {
+                    m.invisible_until = invisible_until;
+                }
+                m.message.clone()
+            })
+            .collect())
     }
 
     async fn delete_message(&self, message: &QueueMessage) -> Result<(), QueueError> {
         let mut messages = self.messages.lock().await;
-        let index = if let Some(index) = messages.iter().position(|m| m.id == message.id) {
+        // Messages without an id can only be told apart by their body
+        let matches = |m: &QueueMessage| match &message.id {
+            Some(_) => m.id == message.id,
+            None => m.id.is_none() && m.body == message.body,
+        };
+        let index = if let Some(index) = messages.iter().position(|m| matches(&m.message)) {
             index
         } else {
             warn!("Message not found, skipping delete");
@@ -51,6 +119,11 @@ impl Queue for LocalMessageQueue {
         messages.remove(index);
         Ok(())
     }
+
+    async fn purge(&self) -> Result<(), QueueError> {
+        self.messages.lock().await.clear();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -91,4 +164,131 @@ mod tests {
             assert_eq!(received[i].body, *msg);
         }
     }
+
+    #[tokio::test]
+    async fn test_purge_empties_queue() {
+        let queue = LocalMessageQueue::new();
+        for msg in ["first", "second", "third"] {
+            queue.send_message(msg.to_string()).await.unwrap();
+        }
+
+        queue.purge().await.unwrap();
+
+        let messages = queue.receive_messages().await.unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_middle_message() {
+        let queue = LocalMessageQueue::new();
+        for msg in ["first", "second", "third"] {
+            queue.send_message(msg.to_string()).await.unwrap();
+        }
+
+        let received = queue.receive_messages().await.unwrap();
+        queue.delete_message(&received[1]).await.unwrap();
+
+        let remaining = queue.receive_messages().await.unwrap();
+        let bodies: Vec<_> = remaining.iter().map(|m| m.body.as_str()).collect();
+        assert_eq!(bodies, ["first", "third"]);
+        assert_eq!(remaining[0].id, received[0].id);
+        assert_eq!(remaining[1].id, received[2].id);
+    }
+
+    #[tokio::test]
+    async fn test_delete_message_without_id_matches_body() {
+        let queue = LocalMessageQueue::new();
+        {
+            let mut messages = queue.messages.lock().await;
+            for body in ["first", "second", "third"] {
+                messages.push(LocalMessage {
+                    message: QueueMessage {
+                        body: body.to_string(),
+                        id: None,
+                    },
+                    expires_at: None,
+                    invisible_until: None,
+                });
+            }
+        }
+
+        queue
+            .delete_message(&QueueMessage {
+                body: "second".to_string(),
+                id: None,
+            })
+            .await
+            .unwrap();
+
+        let bodies: Vec<_> = queue
+            .receive_messages()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.body)
+            .collect();
+        assert_eq!(bodies, ["first", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_unknown_message_is_a_no_op() {
+        let queue = LocalMessageQueue::new();
+        queue.send_message("first".to_string()).await.unwrap();
+
+        queue
+            .delete_message(&QueueMessage {
+                body: "first".to_string(),
+                id: Some("unknown".to_string()),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(queue.receive_messages().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_messages_are_dropped() {
+        let queue = LocalMessageQueue::new().with_message_ttl(Duration::from_secs(60));
+        queue.send_message("stale".to_string()).await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        queue.send_message("fresh".to_string()).await.unwrap();
+        assert_eq!(queue.receive_messages().await.unwrap().len(), 2);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        let received = queue.receive_messages().await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].body, "fresh");
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(queue.receive_messages().await.unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_undeleted_message_is_received_again_after_visibility_timeout() {
+        let queue = LocalMessageQueue::new().with_visibility_timeout(Duration::from_secs(30));
+        queue.send_message("job".to_string()).await.unwrap();
+
+        let received = queue.receive_messages().await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(queue.receive_messages().await.unwrap().is_empty());
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        let redelivered = queue.receive_messages().await.unwrap();
+        assert_eq!(redelivered.len(), 1);
+        assert_eq!(redelivered[0].id, received[0].id);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deleted_message_is_not_received_again_after_visibility_timeout() {
+        let queue = LocalMessageQueue::new().with_visibility_timeout(Duration::from_secs(30));
+        queue.send_message("job".to_string()).await.unwrap();
+
+        let received = queue.receive_messages().await.unwrap();
+        tokio::time::advance(Duration::from_secs(10)).await;
+        queue.delete_message(&received[0]).await.unwrap();
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(queue.receive_messages().await.unwrap().is_empty());
+    }
 }
@@ -13,6 +13,8 @@ pub enum QueueError {
     SendError(String),
     ReceiveError(String),
     DeleteError(String),
+    PurgeError(String),
+    HealthCheckError(String),
 }
 
 impl std::fmt::Display for QueueError {
@@ -21,6 +23,8 @@ impl std::fmt::Display for QueueError {
             Self::SendError(msg) => write!(f, "Failed to send message: {}", msg),
             Self::ReceiveError(msg) => write!(f, "Failed to receive message: {}", msg),
             Self::DeleteError(msg) => write!(f, "Failed to delete message: {}", msg),
+            Self::PurgeError(msg) => write!(f, "Failed to purge queue: {}", msg),
+            Self::HealthCheckError(msg) => write!(f, "Failed to reach queue: {}", msg),
         }
     }
 }
@@ -30,7 +34,34 @@ impl std::fmt::Display for QueueError {
 pub trait Queue {
     async fn send_message(&self, message: String) -> Result<(), QueueError>;
 
+    /// Sends a message, returning the id the queue assigned to it when the queue reports one.
+    async fn send_message_with_id(&self, message: String) -> Result<Option<String>, QueueError> {
+        self.send_message(message).await.map(|()| None)
+    }
+
     async fn receive_messages(&self) -> Result<Vec<QueueMessage>, QueueError>;
 
     async fn delete_message(&self, message: &QueueMessage) -> Result<(), QueueError>;
+
+    /// Checks that the queue can be reached, without touching its messages. The default assumes
+    /// it can, for queues held in memory.
+    async fn check_health(&self) -> Result<(), QueueError> {
+        Ok(())
+    }
+
+    /// Removes every message from the queue. Meant for test setup and admin resets.
+    ///
+    /// The default drains the queue by receiving and deleting until nothing is left, so it
+    /// doesn't return while messages keep arriving.
+    async fn purge(&self) -> Result<(), QueueError> {
+        loop {
+            let messages = self.receive_messages().await?;
+            if messages.is_empty() {
+                return Ok(());
+            }
+            for message in &messages {
+                self.delete_message(message).await?;
+            }
+        }
+    }
 }
@@ -4,22 +4,62 @@ use tracing::{debug, warn};
 
 use super::message_queue::{Queue, QueueError, QueueMessage};
 
+/// Most messages SQS returns from a single receive.
+pub const SQS_MAX_MESSAGES: i32 = 10;
+
+/// Longest SQS lets a receive wait for messages to arrive.
+pub const SQS_MAX_WAIT_TIME_SECONDS: i32 = 20;
+
+/// Queue backed by SQS. Messages expire after the queue's `MessageRetentionPeriod` attribute
+/// (4 days by default), which is configured on the queue itself rather than here.
 #[derive(Debug, Clone)]
 pub struct SqsMessageQueue {
     queue_url: String,
     client: Client,
+    max_messages: i32,
+    wait_time_seconds: i32,
 }
 
 impl SqsMessageQueue {
     pub fn new(queue_url: String, aws_config: aws_config::SdkConfig) -> Self {
         let client = Client::new(&aws_config);
-        Self { client, queue_url }
+        Self {
+            client,
+            queue_url,
+            max_messages: SQS_MAX_MESSAGES,
+            wait_time_seconds: SQS_MAX_WAIT_TIME_SECONDS,
+        }
+    }
+
+    /// Messages taken per receive, clamped to the 1 to 10 SQS allows.
+    pub fn with_max_messages(mut self, max_messages: i32) -> Self {
+        self.max_messages = max_messages.clamp(1, SQS_MAX_MESSAGES);
+        self
+    }
+
+    /// Seconds a receive waits for messages when the queue is empty, clamped to the 0 to 20
+    /// SQS allows. Zero returns at once, so idle polling costs a request per poll.
+    pub fn with_wait_time_seconds(mut self, wait_time_seconds: i32) -> Self {
+        self.wait_time_seconds = wait_time_seconds.clamp(0, SQS_MAX_WAIT_TIME_SECONDS);
+        self
+    }
+
+    pub const fn max_messages(&self) -> i32 {
+        self.max_messages
+    }
+
+    pub const fn wait_time_seconds(&self) -> i32 {
+        self.wait_time_seconds
     }
 }
 
 #[async_trait]
 impl Queue for SqsMessageQueue {
     async fn send_message(&self, message: String) -> Result<(), QueueError> {
+        self.send_message_with_id(message).await.map(|_| ())
+    }
+
+    async fn send_message_with_id(&self, message: String) -> Result<Option<String>, QueueError> {
         match self
             .client
             .send_message()
@@ -28,7 +68,7 @@ impl Queue for SqsMessageQueue {
             .send()
             .await
         {
-            Ok(_) => Ok(()),
+            Ok(output) => Ok(output.message_id),
             Err(e) => {
                 warn!("Error sending message to SQS: {}", e);
                 Err(QueueError::SendError(e.to_string()))
@@ -41,8 +81,8 @@ impl Queue for SqsMessageQueue {
             .client
             .receive_message()
             .queue_url(self.queue_url.clone())
-            .wait_time_seconds(20)
-            .max_number_of_messages(10)
+            .wait_time_seconds(self.wait_time_seconds)
+            .max_number_of_messages(self.max_messages)
             .send()
             .await;
 
@@ -96,4 +136,72 @@ impl Queue for SqsMessageQueue {
             Ok(())
         }
     }
+
+    // Reading the queue's attributes needs the queue to exist and be reachable, but doesn't
+    // receive any message
+    async fn check_health(&self) -> Result<(), QueueError> {
+        match self
+            .client
+            .get_queue_attributes()
+            .queue_url(self.queue_url.clone())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!("Error reaching SQS queue: {}", e);
+                Err(QueueError::HealthCheckError(e.to_string()))
+            }
+        }
+    }
+
+    // SQS purges asynchronously and allows one purge per queue every 60 seconds
+    async fn purge(&self) -> Result<(), QueueError> {
+        match self
+            .client
+            .purge_queue()
+            .queue_url(self.queue_url.clone())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!("Error purging SQS queue: {}", e);
+                Err(QueueError::PurgeError(e.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> SqsMessageQueue {
+        let config = aws_config::SdkConfig::builder()
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .build();
+        SqsMessageQueue::new("test-queue-url".to_string(), config)
+    }
+
+    #[test]
+    fn test_receive_defaults_to_full_batch_and_long_polling() {
+        let queue = queue();
+        assert_eq!(queue.max_messages(), 10);
+        assert_eq!(queue.wait_time_seconds(), 20);
+    }
+
+    #[test]
+    fn test_max_messages_is_clamped() {
+        assert_eq!(queue().with_max_messages(0).max_messages(), 1);
+        assert_eq!(queue().with_max_messages(5).max_messages(), 5);
+        assert_eq!(queue().with_max_messages(20).max_messages(), 10);
+    }
+
+    #[test]
+    fn test_wait_time_is_clamped() {
+        assert_eq!(queue().with_wait_time_seconds(-1).wait_time_seconds(), 0);
+        assert_eq!(queue().with_wait_time_seconds(0).wait_time_seconds(), 0);
+        assert_eq!(queue().with_wait_time_seconds(60).wait_time_seconds(), 20);
+    }
 }
@@ -11,8 +11,11 @@ use starknet::{
 use starknet_crypto::Felt;
 use tracing::{debug, info, instrument, warn};
 
+use crate::hashing::chain_id_from_env;
+
 pub struct StarknetAccount {
     account: SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+    read_only: bool,
 }
 
 impl StarknetAccount {
@@ -21,6 +24,7 @@ impl StarknetAccount {
         provider: Arc<JsonRpcClient<HttpTransport>>,
         account_private_key: &str,
         account_address: &str,
+        chain_id: Felt,
     ) -> Result<Self> {
         debug!("Creating new Starknet account");
 
@@ -31,25 +35,84 @@ impl StarknetAccount {
         let address = Self::felt(account_address)?;
 
         debug!(
-            chain_id = ?chain_id::SEPOLIA,
+            chain_id = ?chain_id,
             encoding = ?ExecutionEncoding::New,
             "Initializing SingleOwnerAccount"
         );
 
-        let account = SingleOwnerAccount::new(
+        let account =
+            SingleOwnerAccount::new(provider, signer, address, chain_id, ExecutionEncoding::New);
+
+        debug!("Starknet account successfully created");
+        Ok(Self {
+            account,
+            read_only: false,
+        })
+    }
+
+    /// In read-only mode, proof verifications are only logged instead of submitted on-chain, so
+    /// the account can be pointed at a real network safely.
+    pub const fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Account signing transactions for Starknet Sepolia.
+    pub fn new_sepolia(
+        provider: Arc<JsonRpcClient<HttpTransport>>,
+        account_private_key: &str,
+        account_address: &str,
+    ) -> Result<Self> {
+        Self::new(
             provider,
-            signer,
-            address,
+            account_private_key,
+            account_address,
             chain_id::SEPOLIA,
-            ExecutionEncoding::New,
-        );
+        )
+    }
 
-        debug!("Starknet account successfully created");
-        Ok(Self { account })
+    /// Account signing transactions for Starknet mainnet.
+    pub fn new_mainnet(
+        provider: Arc<JsonRpcClient<HttpTransport>>,
+        account_private_key: &str,
+        account_address: &str,
+    ) -> Result<Self> {
+        Self::new(
+            provider,
+            account_private_key,
+            account_address,
+            chain_id::MAINNET,
+        )
+    }
+
+    /// Account signing transactions for the chain named by `STARKNET_CHAIN_ID`, defaulting to
+    /// Sepolia.
+    pub fn new_from_env(
+        provider: Arc<JsonRpcClient<HttpTransport>>,
+        account_private_key: &str,
+        account_address: &str,
+    ) -> Result<Self> {
+        Self::new(
+            provider,
+            account_private_key,
+            account_address,
+            chain_id_from_env()?,
+        )
+    }
+
+    /// Chain id the account signs transactions for.
+    pub fn chain_id(&self) -> Felt {
+        self.account.chain_id()
     }
 
+    /// Submits `proof` to the verifier at `verifier_address`, returning the hash of the
+    /// transaction, or `None` in read-only mode, where nothing is submitted.
     #[instrument(skip(self), level = "debug")]
-    pub async fn verify_mmr_proof(&self, verifier_address: &str, proof: Vec<Felt>) -> Result<Felt> {
+    pub async fn verify_mmr_proof(
+        &self,
+        verifier_address: &str,
+        proof: Vec<Felt>,
+    ) -> Result<Option<Felt>> {
         const MAX_RETRIES: u32 = 3;
         const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 
@@ -60,6 +123,15 @@ impl StarknetAccount {
             to: Self::felt(verifier_address)?,
         };
 
+        if self.read_only {
+            info!(
+                verifier = %verifier_address,
+                calldata_len = call.calldata.len(),
+                "Read-only mode, skipping MMR proof verification"
+            );
+            return Ok(None);
+        }
+
         let mut attempt = 0;
         loop {
             match self.account.execute_v3(vec![call.clone()]).send().await {
@@ -68,7 +140,7 @@ impl StarknetAccount {
                         tx_hash = ?tx.transaction_hash,
                         "MMR proof onchain verification successful."
                     );
-                    return Ok(tx.transaction_hash);
+                    return Ok(Some(tx.transaction_hash));
                 }
                 Err(e) => {
                     if attempt >= MAX_RETRIES {
@@ -113,17 +185,41 @@ mod tests {
         let private_key = "0x1234567890abcdef";
         let address = "0x987654321fedcba";
 
-        let result = StarknetAccount::new(provider, private_key, address);
+        let result = StarknetAccount::new(provider, private_key, address, chain_id::SEPOLIA);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_new_account_reports_chain_id() {
+        let private_key = "0x1234567890abcdef";
+        let address = "0x987654321fedcba";
+
+        let sepolia =
+            StarknetAccount::new_sepolia(create_test_provider(), private_key, address).unwrap();
+        assert_eq!(sepolia.chain_id(), chain_id::SEPOLIA);
+
+        let mainnet =
+            StarknetAccount::new_mainnet(create_test_provider(), private_key, address).unwrap();
+        assert_eq!(mainnet.chain_id(), chain_id::MAINNET);
+
+        let devnet_chain_id = Felt::from_hex("0x4b4154414e41").unwrap();
+        let devnet = StarknetAccount::new(
+            create_test_provider(),
+            private_key,
+            address,
+            devnet_chain_id,
+        )
+        .unwrap();
+        assert_eq!(devnet.chain_id(), devnet_chain_id);
+    }
+
     #[test]
     fn test_new_account_invalid_private_key() {
         let provider = create_test_provider();
         let private_key = "invalid_key";
         let address = "0x987654321fedcba";
 
-        let result = StarknetAccount::new(provider, private_key, address);
+        let result = StarknetAccount::new(provider, private_key, address, chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
@@ -133,29 +229,34 @@ mod tests {
         let private_key = "0x1234567890abcdef";
         let address = "invalid_address";
 
-        let result = StarknetAccount::new(provider, private_key, address);
+        let result = StarknetAccount::new(provider, private_key, address, chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_new_account_empty_private_key() {
         let provider = create_test_provider();
-        let result = StarknetAccount::new(provider, "", "0x987654321fedcba");
+        let result = StarknetAccount::new(provider, "", "0x987654321fedcba", chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_new_account_empty_address() {
         let provider = create_test_provider();
-        let result = StarknetAccount::new(provider, "0x1234567890abcdef", "");
+        let result = StarknetAccount::new(provider, "0x1234567890abcdef", "", chain_id::SEPOLIA);
         assert!(result.is_err());
     }
 
     #[tokio::test]
     async fn test_verify_mmr_proof_success() {
         let provider = create_test_provider();
-        let account =
-            StarknetAccount::new(provider, "0x1234567890abcdef", "0x987654321fedcba").unwrap();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
 
         let verifier_address = "0x123456789";
         let proof = vec![Felt::from_str("0x1").unwrap()];
@@ -169,10 +270,35 @@ mod tests {
     #[tokio::test]
     async fn test_verify_mmr_proof_empty_proof() {
         let provider = create_test_provider();
-        let account =
-            StarknetAccount::new(provider, "0x1234567890abcdef", "0x987654321fedcba").unwrap();
+        let account = StarknetAccount::new(
+            provider,
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap();
 
         let result = account.verify_mmr_proof("0x123456789", vec![]).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_verify_mmr_proof_skips_submission_in_read_only_mode() {
+        let account = StarknetAccount::new(
+            create_test_provider(),
+            "0x1234567890abcdef",
+            "0x987654321fedcba",
+            chain_id::SEPOLIA,
+        )
+        .unwrap()
+        .with_read_only(true);
+
+        // Nothing reaches the dummy provider, which would fail the submission
+        let proof = vec![Felt::from_str("0x1").unwrap()];
+        let result = account.verify_mmr_proof("0x123456789", proof).await;
+        assert_eq!(result.unwrap(), None);
+
+        // The verifier address is still checked
+        assert!(account.verify_mmr_proof("not hex", vec![]).await.is_err());
+    }
 }
@@ -1,16 +1,168 @@
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
-use starknet::core::types::TransactionExecutionStatus;
-use starknet::core::types::TransactionReceipt::Invoke;
-use starknet::providers::Provider;
+use starknet::core::types::{Felt, StarknetError, TransactionExecutionStatus};
+use starknet::providers::ProviderError;
 
-use crate::hashing::HashingProviderTrait;
+use crate::hashing::{HashingProviderTrait, StoredHash};
+use crate::time::{
+    HOUR_SECS, ProofTimestampRanges, expected_fee_points, hour_index, last_fee_point,
+};
 use std::marker::{Send, Sync};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Default bound on concurrent `hash_avg_fees_and_store` submissions.
+pub const DEFAULT_MAX_CONCURRENT_SUBMISSIONS: usize = 8;
+
+/// How to retry fetching the status of a freshly submitted transaction the node doesn't know
+/// about yet. Any other error fails immediately.
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiptRetry {
+    /// Total attempts, including the first one.
+    pub attempts: u32,
+    /// Delay before the first retry, doubled on every subsequent one.
+    pub initial_delay: Duration,
+}
+
+impl Default for ReceiptRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// How long [`HashingService::run`] waits for the light client to catch up on the fees of its
+/// range, polling every `poll_interval`. The default doesn't wait, failing on missing fees.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeWait {
+    pub poll_interval: Duration,
+    pub max_wait: Duration,
+}
+
+impl Default for FeeWait {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(30),
+            max_wait: Duration::ZERO,
+        }
+    }
+}
+
+/// Default number of retries a single [`HashingService::run`] may spend across all of its
+/// on-chain operations.
+pub const DEFAULT_RETRY_BUDGET: u32 = 32;
+
+/// Total retries allowed across every on-chain operation of a run. Operations still retry
+/// according to their own policy, but once the budget is spent they fail on the next error
+/// instead of retrying, so a pathological run can't keep retrying for hours. Clones share
+/// the same budget.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    remaining: Arc<AtomicU32>,
+}
+
+impl RetryBudget {
+    pub fn new(retries: u32) -> Self {
+        Self {
+            remaining: Arc::new(AtomicU32::new(retries)),
+        }
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.remaining.load(Ordering::SeqCst)
+    }
+
+    /// Takes one retry from the budget, returning `false` if it is already spent.
+    pub fn try_spend(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .is_ok()
+    }
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETRY_BUDGET)
+    }
+}
+
+/// File recording the start of the last window a [`HashingService::run_range`] backfill
+/// completed, so a backfill interrupted by a crash can resume after it.
+#[derive(Debug, Clone)]
+pub struct BackfillCursor {
+    path: PathBuf,
+}
+
+impl BackfillCursor {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Start of the last completed window, `None` if no progress was recorded yet.
+    pub fn load(&self) -> Result<Option<u64>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => {
+                contents.trim().parse().map(Some).map_err(|e| {
+                    format!("Invalid backfill cursor in {}: {}", self.path.display(), e)
+                })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(err_to_string(e)),
+        }
+    }
+
+    /// Records `window_start` as completed. The cursor is replaced atomically, so a crash
+    /// while recording leaves the previous one intact.
+    pub fn record(&self, window_start: u64) -> Result<(), String> {
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, window_start.to_string()).map_err(err_to_string)?;
+        std::fs::rename(&tmp_path, &self.path).map_err(err_to_string)
+    }
+}
+
+/// Computes locally the hash the hash storage contract stores for a batch of fees.
+pub type FeeHasher = fn(&[f64]) -> StoredHash;
+
+/// A batch whose stored hash differs from the one computed from the fees the light client
+/// returns for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Start of the batch.
+    pub timestamp: u64,
+    pub stored: StoredHash,
+    pub computed: StoredHash,
+}
+
+/// How many of the fees a run needs the light client already returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeAvailability {
+    /// Every required fee is available.
+    Complete,
+    /// The light client hasn't caught up yet: only `have` of the `need` fees are available.
+    Partial { have: usize, need: usize },
+    /// None of the required fees are available yet.
+    Empty,
+}
 
 pub struct HashingService<T: HashingProviderTrait + Sync + Send + 'static> {
     hashing_provider: Arc<T>,
     required_avg_fees_length: usize,
     hash_batch_size: usize,
+    read_only: bool,
+    skip_hashed_batches: bool,
+    receipt_retry: ReceiptRetry,
+    fee_wait: FeeWait,
+    submission_permits: Arc<Semaphore>,
+    backfill_cursor: Option<BackfillCursor>,
+    fee_hasher: FeeHasher,
 }
 
 // Move the helper function to the module level
@@ -18,6 +170,48 @@ fn err_to_string<E: std::fmt::Display>(err: E) -> String {
     format!("{}", err)
 }
 
+const fn is_transaction_not_found(err: &ProviderError) -> bool {
+    matches!(
+        err,
+        ProviderError::StarknetError(StarknetError::TransactionHashNotFound)
+    )
+}
+
+async fn wait_for_transaction_status<T: HashingProviderTrait + Sync + Send + 'static>(
+    hashing_provider: Arc<T>,
+    transaction_hash: Felt,
+    retry: ReceiptRetry,
+    retry_budget: RetryBudget,
+) -> Result<TransactionExecutionStatus, String> {
+    let mut delay = retry.initial_delay;
+    let mut attempt = 1;
+    loop {
+        match hashing_provider
+            .get_transaction_status(transaction_hash)
+            .await
+        {
+            Ok(status) => return Ok(status),
+            Err(err) if is_transaction_not_found(&err) && attempt < retry.attempts => {
+                if !retry_budget.try_spend() {
+                    warn!(
+                        "Retry budget spent, giving up on transaction {:#x}",
+                        transaction_hash
+                    );
+                    return Err(err_to_string(err));
+                }
+                debug!(
+                    "Transaction {:#x} not found yet (attempt {}/{}), retrying in {:?}",
+                    transaction_hash, attempt, retry.attempts, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err_to_string(err)),
+        }
+    }
+}
+
 impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
     pub fn new(
         hashing_service: T,
@@ -28,47 +222,315 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
             hashing_provider: hashing_service.into(),
             required_avg_fees_length,
             hash_batch_size,
+            read_only: false,
+            skip_hashed_batches: true,
+            receipt_retry: ReceiptRetry::default(),
+            fee_wait: FeeWait::default(),
+            submission_permits: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_SUBMISSIONS)),
+            backfill_cursor: None,
+            fee_hasher: StoredHash::of_avg_fees,
         }
     }
 
-    pub async fn run(&self, start_timestamp: u64) -> Result<(), String> {
-        let end_timestamp = start_timestamp + 3600 * (self.required_avg_fees_length as u64 - 1);
-        self.check_avg_fees_availability(start_timestamp, end_timestamp)
-            .await?;
+    /// Builds a service checking the hourly fees of the overall span of `ranges`, the data the
+    /// prover requests for them: `(end - start) / 3600 + 1` fees. Fails when the span isn't a
+    /// whole number of hours, as its fees wouldn't line up with the stored ones.
+    pub fn from_range(
+        hashing_service: T,
+        ranges: &ProofTimestampRanges,
+        hash_batch_size: usize,
+    ) -> Result<Self, String> {
+        let (start, end) = ranges.overall();
+        if end < start || (end - start) % HOUR_SECS != 0 {
+            return Err(format!(
+                "Range from {} to {} doesn't span a whole number of hours",
+                start, end
+            ));
+        }
+
+        Ok(Self::new(
+            hashing_service,
+            expected_fee_points(start, end, HOUR_SECS),
+            hash_batch_size,
+        ))
+    }
+
+    /// Bounds how many batch hash submissions are in flight at once, so a long backfill doesn't
+    /// flood the node or race on the account nonce.
+    pub fn with_max_concurrent_submissions(mut self, max_concurrent_submissions: usize) -> Self {
+        self.submission_permits = Arc::new(Semaphore::new(max_concurrent_submissions.max(1)));
+        self
+    }
+
+    pub const fn with_receipt_retry(mut self, receipt_retry: ReceiptRetry) -> Self {
+        self.receipt_retry = receipt_retry;
+        self
+    }
+
+    pub const fn with_fee_wait(mut self, fee_wait: FeeWait) -> Self {
+        self.fee_wait = fee_wait;
+        self
+    }
+
+    /// Records the progress of [`Self::run_range`] in `backfill_cursor`. Without it, backfills
+    /// can't be resumed.
+    pub fn with_backfill_cursor(mut self, backfill_cursor: BackfillCursor) -> Self {
+        self.backfill_cursor = Some(backfill_cursor);
+        self
+    }
+
+    /// Hash function against which [`Self::verify_stored_hashes`] checks the stored hashes,
+    /// [`StoredHash::of_avg_fees`], the hash storage contract's, unless set.
+    pub const fn with_fee_hasher(mut self, fee_hasher: FeeHasher) -> Self {
+        self.fee_hasher = fee_hasher;
+        self
+    }
+
+    /// In read-only mode, reads are still made but every on-chain submission is skipped and only
+    /// logged, so the service can be pointed at a real network safely.
+    pub const fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// When the batch hash is already stored, the per-batch scan is skipped: the batch hash
+    /// can't have been stored without the hashes of its members. Disabling this always scans.
+    pub const fn with_skip_hashed_batches(mut self, skip_hashed_batches: bool) -> Self {
+        self.skip_hashed_batches = skip_hashed_batches;
+        self
+    }
+
+    /// Makes sure the hashes of the fees from `start_timestamp` are stored on-chain. Retries of
+    /// every on-chain operation are taken from `retry_budget`.
+    pub async fn run(
+        &self,
+        start_timestamp: u64,
+        retry_budget: &RetryBudget,
+    ) -> Result<(), String> {
+        // Fees are stored per hour, so batches must start on an hour boundary
+        if hour_index(0, start_timestamp, HOUR_SECS).is_none() {
+            return Err(format!(
+                "start_timestamp {} is not aligned to the hour",
+                start_timestamp
+            ));
+        }
+
+        let end_timestamp =
+            last_fee_point(start_timestamp, self.required_avg_fees_length, HOUR_SECS)
+                .ok_or_else(|| "required_avg_fees_length must be positive".to_string())?;
+        self.await_fees_available(
+            start_timestamp,
+            end_timestamp,
+            self.fee_wait.poll_interval,
+            self.fee_wait.max_wait,
+            retry_budget,
+        )
+        .await?;
+
+        // Storing the per-batch hashes doesn't store the batch hash, so a missing batch hash
+        // found here is still missing once they are stored
+        let batch_hash_available = if self.skip_hashed_batches {
+            let available = self
+                .is_batch_hash_avg_fees_available(start_timestamp)
+                .await?;
+            if available {
+                debug!(
+                    "Batch hash for timestamp {} already stored, skipping per-batch scan",
+                    start_timestamp
+                );
+                return Ok(());
+            }
+            Some(available)
+        } else {
+            None
+        };
+
         let unavailable_batch_timestamp_hashes = self
             .get_unavailable_batch_timestamp_hashes(start_timestamp, end_timestamp)
             .await?;
 
         if !unavailable_batch_timestamp_hashes.is_empty() {
-            self.hash_and_store_avg_fees_onchain(unavailable_batch_timestamp_hashes)
+            self.hash_and_store_avg_fees_onchain(unavailable_batch_timestamp_hashes, retry_budget)
                 .await?;
         }
 
-        if !self
-            .is_batch_hash_avg_fees_available(start_timestamp)
-            .await?
-        {
-            self.hash_batch_avg_fees_onchain(start_timestamp).await?;
+        let batch_hash_available = match batch_hash_available {
+            Some(available) => available,
+            None => {
+                self.is_batch_hash_avg_fees_available(start_timestamp)
+                    .await?
+            }
+        };
+        if !batch_hash_available {
+            self.hash_batch_avg_fees_onchain(start_timestamp, retry_budget)
+                .await?;
         }
 
         Ok(())
     }
 
-    async fn check_avg_fees_availability(
+    /// Backfills the hashes of every window of `required_avg_fees_length` hours from
+    /// `start_timestamp` until `end_timestamp`, one window after the other, recording each
+    /// completed window in the backfill cursor. With `resume`, windows up to the one the cursor
+    /// records are skipped. Retries of every on-chain operation are taken from `retry_budget`.
+    pub async fn run_range(
         &self,
         start_timestamp: u64,
         end_timestamp: u64,
+        resume: bool,
+        retry_budget: &RetryBudget,
     ) -> Result<(), String> {
-        let avg_fees = self
+        let window_secs = HOUR_SECS * self.required_avg_fees_length as u64;
+        if window_secs == 0 {
+            return Err("required_avg_fees_length must be positive".to_string());
+        }
+
+        let mut window_start = start_timestamp;
+        if resume {
+            let cursor = match &self.backfill_cursor {
+                Some(backfill_cursor) => backfill_cursor.load()?,
+                None => return Err("Can't resume a backfill without a cursor".to_string()),
+            };
+            match cursor {
+                // Only a window of this range can be resumed after
+                Some(completed)
+                    if (start_timestamp..end_timestamp).contains(&completed)
+                        && (completed - start_timestamp).is_multiple_of(window_secs) =>
+                {
+                    info!("Resuming backfill after window starting at {}", completed);
+                    window_start = completed + window_secs;
+                }
+                Some(completed) => warn!(
+                    "Backfill cursor {} is not a window from {} to {}, starting over",
+                    completed, start_timestamp, end_timestamp
+                ),
+                None => debug!(
+                    "No backfill progress recorded, starting from {}",
+                    start_timestamp
+                ),
+            }
+        }
+
+        while window_start < end_timestamp {
+            self.run(window_start, retry_budget).await?;
+            if let Some(backfill_cursor) = &self.backfill_cursor {
+                backfill_cursor.record(window_start)?;
+            }
+            window_start += window_secs;
+        }
+
+        Ok(())
+    }
+
+    /// Recomputes the hash of every stored batch from `start_timestamp` until `end_timestamp`
+    /// from the fees the light client returns for it, reporting the batches whose stored hash
+    /// differs. Batches without a stored hash are skipped. Retries of the fee reads are taken
+    /// from `retry_budget`.
+    pub async fn verify_stored_hashes(
+        &self,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        retry_budget: &RetryBudget,
+    ) -> Result<Vec<Mismatch>, String> {
+        let mut mismatches = Vec::new();
+        let batch_secs = HOUR_SECS * self.hash_batch_size as u64;
+        for t in (start_timestamp..end_timestamp).step_by(batch_secs.max(1) as usize) {
+            let stored = self
+                .hashing_provider
+                .get_hash_stored_avg_fees(t)
+                .await
+                .map_err(err_to_string)?;
+            if stored.is_zero() {
+                debug!("No hash stored for timestamp {}, skipping it", t);
+                continue;
+            }
+
+            let batch_end = last_fee_point(t, self.hash_batch_size, HOUR_SECS)
+                .ok_or_else(|| "hash_batch_size must be positive".to_string())?;
+            let avg_fees = self
+                .hashing_provider
+                .get_avg_fees_in_range(t, batch_end, retry_budget)
+                .await
+                .map_err(err_to_string)?;
+
+            let computed = (self.fee_hasher)(&avg_fees);
+            if computed != stored {
+                warn!(
+                    "Stored hash {} for timestamp {} doesn't match computed hash {}",
+                    stored, t, computed
+                );
+                mismatches.push(Mismatch {
+                    timestamp: t,
+                    stored,
+                    computed,
+                });
+            }
+        }
+
+        Ok(mismatches)
+    }
+
+    /// Reports how many of the fees from `start_timestamp` to `end_timestamp` the light client
+    /// already returns, so callers can wait for it to catch up on a partial range instead of
+    /// failing. Getting more fees than required is an error, as they can't line up with the range.
+    pub async fn check_fee_availability(
+        &self,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        retry_budget: &RetryBudget,
+    ) -> Result<FeeAvailability, String> {
+        let have = self
             .hashing_provider
-            .get_avg_fees_in_range(start_timestamp, end_timestamp)
+            .get_avg_fees_in_range(start_timestamp, end_timestamp, retry_budget)
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| e.to_string())?
+            .len();
+        let need = self.required_avg_fees_length;
 
-        if avg_fees.len() != self.required_avg_fees_length {
-            return Err("avg_fees_len is not equal to required_avg_fees_length".to_string());
+        match have {
+            0 => Ok(FeeAvailability::Empty),
+            have if have < need => Ok(FeeAvailability::Partial { have, need }),
+            have if have == need => Ok(FeeAvailability::Complete),
+            have => Err(format!(
+                "Light client returned {} fees, more than the {} required",
+                have, need
+            )),
+        }
+    }
+
+    /// Polls every `poll_interval` until all the fees from `start_timestamp` to `end_timestamp`
+    /// are available, waiting for the light client to catch up. Fails once they still aren't
+    /// after `max_wait`, a zero `max_wait` checking only once.
+    pub async fn await_fees_available(
+        &self,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        poll_interval: Duration,
+        max_wait: Duration,
+        retry_budget: &RetryBudget,
+    ) -> Result<(), String> {
+        let deadline = Instant::now() + max_wait;
+        loop {
+            let availability = self
+                .check_fee_availability(start_timestamp, end_timestamp, retry_budget)
+                .await?;
+            if availability == FeeAvailability::Complete {
+                return Ok(());
+            }
+
+            if Instant::now() + poll_interval > deadline {
+                debug!(
+                    "Fees from {} to {} not fully available: {:?}",
+                    start_timestamp, end_timestamp, availability
+                );
+                return Err("avg_fees_len is not equal to required_avg_fees_length".to_string());
+            }
+            info!(
+                "Fees from {} to {} not fully available ({:?}), waiting {:?} for the light client to catch up",
+                start_timestamp, end_timestamp, availability, poll_interval
+            );
+            tokio::time::sleep(poll_interval).await;
         }
-        Ok(())
     }
 
     async fn get_unavailable_batch_timestamp_hashes(
@@ -78,16 +540,17 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
     ) -> Result<Vec<u64>, String> {
         let mut unavailable_batch_timestamp_hashes = Vec::new();
 
-        for t in (start_timestamp..end_timestamp).step_by(3600 * self.hash_batch_size) {
+        let batch_secs = HOUR_SECS * self.hash_batch_size as u64;
+        for t in (start_timestamp..end_timestamp).step_by(batch_secs as usize) {
             let hash = self.hashing_provider.get_hash_stored_avg_fees(t).await;
             if let Err(err) = hash {
                 return Err(err.to_string());
             }
 
-            if let Ok(hash_value) = hash {
-                if hash_value == [0; 8] {
-                    unavailable_batch_timestamp_hashes.push(t);
-                }
+            if let Ok(hash_value) = hash
+                && hash_value.is_zero()
+            {
+                unavailable_batch_timestamp_hashes.push(t);
             }
         }
 
@@ -99,12 +562,28 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
     async fn hash_and_store_avg_fees_onchain(
         &self,
         unavailable_batch_timestamp_hashes: Vec<u64>,
+        retry_budget: &RetryBudget,
     ) -> Result<(), String> {
+        if self.read_only {
+            info!(
+                "Read-only mode, skipping hash_avg_fees_and_store for timestamps {:?}",
+                unavailable_batch_timestamp_hashes
+            );
+            return Ok(());
+        }
+
         let tasks = unavailable_batch_timestamp_hashes
             .into_iter()
             .map(|t| {
                 let hashing_service = self.hashing_provider.clone();
-                tokio::task::spawn(async move { hashing_service.hash_avg_fees_and_store(t).await })
+                let submission_permits = self.submission_permits.clone();
+                tokio::task::spawn(async move {
+                    let _permit = submission_permits
+                        .acquire_owned()
+                        .await
+                        .map_err(err_to_string)?;
+                    hashing_service.hash_avg_fees_and_store(t).await
+                })
             })
             .collect::<Vec<_>>();
 
@@ -118,13 +597,14 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
         for receipt in receipts {
             let tx_receipt = receipt?;
             let hashing_service = self.hashing_provider.clone();
+            let receipt_retry = self.receipt_retry;
 
-            let task = tokio::task::spawn(async move {
-                hashing_service
-                    .get_provider()
-                    .get_transaction_receipt(tx_receipt.transaction_hash)
-                    .await
-            });
+            let task = tokio::task::spawn(wait_for_transaction_status(
+                hashing_service,
+                tx_receipt.transaction_hash,
+                receipt_retry,
+                retry_budget.clone(),
+            ));
             invoke_tx_tasks.push(task);
         }
 
@@ -136,18 +616,8 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
         }
 
         for invoke_tx_result in invoke_tx_results {
-            if let Err(err) = invoke_tx_result {
-                return Err(err.to_string());
-            }
-
-            if let Ok(result) = invoke_tx_result {
-                if let Invoke(invoke_receipt) = result.receipt {
-                    if invoke_receipt.execution_result.status()
-                        == TransactionExecutionStatus::Reverted
-                    {
-                        return Err("invoke reverted".to_string());
-                    }
-                }
+            if invoke_tx_result? == TransactionExecutionStatus::Reverted {
+                return Err("invoke reverted".to_string());
             }
         }
 
@@ -164,10 +634,22 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
             Err(err) => return Err(err_to_string(err)),
         };
 
-        Ok(hash != [0; 8])
+        Ok(!hash.is_zero())
     }
 
-    async fn hash_batch_avg_fees_onchain(&self, start_timestamp: u64) -> Result<(), String> {
+    async fn hash_batch_avg_fees_onchain(
+        &self,
+        start_timestamp: u64,
+        retry_budget: &RetryBudget,
+    ) -> Result<(), String> {
+        if self.read_only {
+            info!(
+                "Read-only mode, skipping hash_batched_avg_fees for timestamp {}",
+                start_timestamp
+            );
+            return Ok(());
+        }
+
         // if everything is successful, we perform batch hash of hash of avg gas fee
         let batch_hash_invoke_res = match self
             .hashing_provider
@@ -179,17 +661,15 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
         };
 
         // check if it has been successfully stored onchain
-        let receipt = match self
-            .hashing_provider
-            .get_provider()
-            .get_transaction_receipt(batch_hash_invoke_res.transaction_hash)
-            .await
-        {
-            Ok(receipt) => receipt,
-            Err(err) => return Err(err_to_string(err)),
-        };
+        let status = wait_for_transaction_status(
+            self.hashing_provider.clone(),
+            batch_hash_invoke_res.transaction_hash,
+            self.receipt_retry,
+            retry_budget.clone(),
+        )
+        .await?;
 
-        if receipt.receipt.execution_result().status() == TransactionExecutionStatus::Reverted {
+        if status == TransactionExecutionStatus::Reverted {
             return Err("batch hash reverted".to_string());
         }
 
@@ -200,119 +680,216 @@ impl<T: HashingProviderTrait + Sync + Send + 'static> HashingService<T> {
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
-    use async_trait::async_trait;
     use starknet::{
-        core::types::{Felt, InvokeTransactionResult},
-        providers::{JsonRpcClient, ProviderError, jsonrpc::HttpTransport},
+        core::types::{Felt, TransactionExecutionStatus},
+        providers::ProviderError,
     };
+    use tempfile::TempDir;
+
+    use crate::test_util::{MOCK_SUBMISSION_ERROR, MockHashingProvider, transaction_not_found};
+    use crate::time::{HOUR_SECS, ProofTimestampRanges};
 
-    use crate::hashing::HashingProviderTrait;
+    use super::{
+        BackfillCursor, FeeAvailability, FeeWait, HashingService, Mismatch, ReceiptRetry,
+        RetryBudget, wait_for_transaction_status,
+    };
+    use crate::hashing::StoredHash;
 
-    use super::HashingService;
+    const TEST_RECEIPT_RETRY: ReceiptRetry = ReceiptRetry {
+        attempts: 3,
+        initial_delay: Duration::from_millis(1),
+    };
 
-    // use crate::{hashing::HashingProcess, services::hashing_service::HashingServiceTrait};
+    const REQUIRED_AVG_FEES_LENGTH: usize = 10;
+    const HASH_BATCH_SIZE: usize = 10;
 
-    struct MockHashingProvider {
-        avg_fees: Vec<f64>,
-        hash_stored_avg_fees: [u32; 8],
-        hash_batched_avg_fee: [u32; 8],
+    fn setup() -> HashingService<MockHashingProvider> {
+        setup_with(MockHashingProvider::new())
     }
 
-    impl MockHashingProvider {
-        pub fn new() -> Self {
-            Self {
-                avg_fees: vec![],
-                hash_stored_avg_fees: [0; 8],
-                hash_batched_avg_fee: [0; 8],
-            }
-        }
+    fn setup_with(provider: MockHashingProvider) -> HashingService<MockHashingProvider> {
+        HashingService::new(provider, REQUIRED_AVG_FEES_LENGTH, HASH_BATCH_SIZE)
+    }
 
-        pub fn set_avg_fees(&mut self, avg_fees: Vec<f64>) {
-            self.avg_fees = avg_fees;
-        }
+    fn provider_with_fees() -> MockHashingProvider {
+        MockHashingProvider::new().with_avg_fees(vec![1.0; REQUIRED_AVG_FEES_LENGTH])
+    }
 
-        pub fn set_hash_batched_avg_fee(&mut self, hash_batched_avg_fee: [u32; 8]) {
-            self.hash_batched_avg_fee = hash_batched_avg_fee;
+    fn ranges(start: u64, end: u64) -> ProofTimestampRanges {
+        ProofTimestampRanges {
+            twap: (start + 3600, end),
+            reserve_price: (start, end),
+            max_return: (start, end - 3600),
         }
     }
 
-    #[async_trait]
-    impl HashingProviderTrait for MockHashingProvider {
-        fn get_provider(&self) -> &JsonRpcClient<HttpTransport> {
-            todo!()
-        }
+    #[test]
+    fn from_range_derives_required_avg_fees_length_from_exact_span() {
+        let process = HashingService::from_range(
+            MockHashingProvider::new(),
+            &ranges(7200, 7200 + 3600 * 23),
+            HASH_BATCH_SIZE,
+        )
+        .unwrap();
 
-        fn get_fossil_light_client_address(&self) -> &Felt {
-            todo!()
-        }
+        assert_eq!(process.required_avg_fees_length, 24);
+        assert_eq!(process.hash_batch_size, HASH_BATCH_SIZE);
+    }
 
-        fn get_hash_storage_address(&self) -> &Felt {
-            todo!()
-        }
+    #[test]
+    fn from_range_fails_for_misaligned_span() {
+        let res = HashingService::from_range(
+            MockHashingProvider::new(),
+            &ranges(7200, 7200 + 3600 * 23 + 1800),
+            HASH_BATCH_SIZE,
+        );
 
-        async fn get_avg_fees_in_range(
-            &self,
-            _start_timestamp: u64,
-            _end_timestamp: u64,
-        ) -> Result<Vec<f64>, ProviderError> {
-            Ok(self.avg_fees.clone())
-        }
+        assert_eq!(
+            res.err().unwrap(),
+            "Range from 7200 to 91800 doesn't span a whole number of hours"
+        );
+    }
 
-        async fn get_hash_stored_avg_fees(
-            &self,
-            _timestamp: u64,
-        ) -> Result<[u32; 8], ProviderError> {
-            Ok(self.hash_stored_avg_fees)
-        }
+    #[tokio::test]
+    async fn should_fail_if_await_fees_available_not_equals_to_required_avg_fees_length() {
+        let process = setup();
 
-        async fn get_hash_batched_avg_fees(
-            &self,
-            _start_timestamp: u64,
-        ) -> Result<[u32; 8], ProviderError> {
-            Ok(self.hash_batched_avg_fee)
-        }
+        let res = process
+            .await_fees_available(
+                0,
+                0,
+                Duration::from_secs(1),
+                Duration::ZERO,
+                &RetryBudget::default(),
+            )
+            .await;
+        assert!(res.err().unwrap() == *"avg_fees_len is not equal to required_avg_fees_length");
+    }
 
-        async fn hash_avg_fees_and_store(
-            &self,
-            _start_timestamp: u64,
-        ) -> Result<InvokeTransactionResult, String> {
-            todo!()
-        }
+    #[tokio::test]
+    async fn should_return_ok_if_await_fees_available_equals_to_required_avg_fees_length() {
+        let process = setup_with(provider_with_fees());
 
-        async fn hash_batched_avg_fees(
-            &self,
-            _start_timestamp: u64,
-        ) -> Result<InvokeTransactionResult, String> {
-            todo!()
-        }
+        let res = process
+            .await_fees_available(
+                0,
+                0,
+                Duration::from_secs(1),
+                Duration::ZERO,
+                &RetryBudget::default(),
+            )
+            .await;
+        assert!(res.is_ok());
     }
 
-    const REQUIRED_AVG_FEES_LENGTH: usize = 10;
-    const HASH_BATCH_SIZE: usize = 10;
+    #[tokio::test(start_paused = true)]
+    async fn should_wait_for_partial_fees_to_become_complete() {
+        let provider = provider_with_fees();
+        provider.push_avg_fees(vec![]);
+        provider.push_avg_fees(vec![1.0; 4]);
+        let process = setup_with(provider);
 
-    fn setup() -> HashingService<MockHashingProvider> {
-        let hashing_service = MockHashingProvider::new();
-        HashingService::new(hashing_service, REQUIRED_AVG_FEES_LENGTH, HASH_BATCH_SIZE)
+        let res = process
+            .await_fees_available(
+                0,
+                0,
+                Duration::from_secs(10),
+                Duration::from_secs(60),
+                &RetryBudget::default(),
+            )
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(process.hashing_provider.reads(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_fail_if_fees_are_not_available_within_max_wait() {
+        let process = setup_with(MockHashingProvider::new().with_avg_fees(vec![1.0; 4]));
+
+        let res = process
+            .await_fees_available(
+                0,
+                0,
+                Duration::from_secs(10),
+                Duration::from_secs(25),
+                &RetryBudget::default(),
+            )
+            .await;
+        assert_eq!(
+            res.unwrap_err(),
+            "avg_fees_len is not equal to required_avg_fees_length"
+        );
+        // Polled at 0s, 10s and 20s, a poll at 30s would be past the deadline
+        assert_eq!(process.hashing_provider.reads(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_run_once_the_light_client_caught_up() {
+        let provider = provider_with_fees()
+            .with_hash_stored_avg_fees([1; 8])
+            .with_hash_batched_avg_fees([1; 8]);
+        provider.push_avg_fees(vec![1.0; 4]);
+        let process = setup_with(provider).with_fee_wait(FeeWait {
+            poll_interval: Duration::from_secs(10),
+            max_wait: Duration::from_secs(60),
+        });
+
+        let res = process.run(0, &RetryBudget::default()).await;
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_report_complete_fee_availability() {
+        let process = setup_with(provider_with_fees());
+
+        let res = process
+            .check_fee_availability(0, 0, &RetryBudget::default())
+            .await;
+        assert_eq!(res.unwrap(), FeeAvailability::Complete);
     }
 
     #[tokio::test]
-    async fn should_fail_if_check_avg_fees_availability_not_equals_to_required_avg_fees_length() {
+    async fn should_report_partial_fee_availability() {
+        let process = setup_with(MockHashingProvider::new().with_avg_fees(vec![1.0; 4]));
+
+        let res = process
+            .check_fee_availability(0, 0, &RetryBudget::default())
+            .await;
+        assert_eq!(
+            res.unwrap(),
+            FeeAvailability::Partial {
+                have: 4,
+                need: REQUIRED_AVG_FEES_LENGTH
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_empty_fee_availability() {
         let process = setup();
 
-        let res = process.check_avg_fees_availability(0, 0).await;
-        assert!(res.err().unwrap() == *"avg_fees_len is not equal to required_avg_fees_length");
+        let res = process
+            .check_fee_availability(0, 0, &RetryBudget::default())
+            .await;
+        assert_eq!(res.unwrap(), FeeAvailability::Empty);
     }
 
     #[tokio::test]
-    async fn should_return_ok_if_check_avg_fees_availability_equals_to_required_avg_fees_length() {
-        let mut process = setup();
+    async fn should_fail_fee_availability_with_more_fees_than_required() {
+        let process =
+            setup_with(
+                MockHashingProvider::new().with_avg_fees(vec![1.0; REQUIRED_AVG_FEES_LENGTH + 1]),
+            );
 
-        Arc::get_mut(&mut process.hashing_provider)
-            .unwrap()
-            .set_avg_fees(vec![1.0; REQUIRED_AVG_FEES_LENGTH]);
-        let res = process.check_avg_fees_availability(0, 0).await;
-        assert!(res.is_ok());
+        let res = process
+            .check_fee_availability(0, 0, &RetryBudget::default())
+            .await;
+        assert_eq!(
+            res.unwrap_err(),
+            "Light client returned 11 fees, more than the 10 required"
+        );
     }
 
     #[tokio::test]
@@ -325,6 +902,55 @@ mod tests {
         assert_eq!(res.unwrap(), vec![0, 3600 * HASH_BATCH_SIZE as u64]);
     }
 
+    #[tokio::test]
+    async fn should_fail_if_start_timestamp_is_not_aligned_to_the_hour() {
+        let process = setup();
+
+        let res = process.run(1800, &RetryBudget::default()).await;
+        assert_eq!(
+            res.unwrap_err(),
+            "start_timestamp 1800 is not aligned to the hour"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_submit_missing_hashes_when_not_read_only() {
+        let process = setup_with(provider_with_fees());
+
+        let res = process.run(0, &RetryBudget::default()).await;
+        assert_eq!(res.unwrap_err(), MOCK_SUBMISSION_ERROR);
+        assert_eq!(process.hashing_provider.submissions(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_skip_submissions_in_read_only_mode() {
+        let process = setup_with(provider_with_fees()).with_read_only(true);
+
+        let res = process.run(0, &RetryBudget::default()).await;
+        assert!(res.is_ok());
+        assert_eq!(process.hashing_provider.submissions(), 0);
+        // avg fees, stored hash and batched hash are still read
+        assert_eq!(process.hashing_provider.reads(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_skip_per_batch_scan_when_batch_hash_is_stored() {
+        for (skip_hashed_batches, expected_stored_hash_reads) in [(true, 0), (false, 1)] {
+            let provider = provider_with_fees()
+                .with_hash_stored_avg_fees([1; 8])
+                .with_hash_batched_avg_fees([1; 8]);
+            let process = setup_with(provider).with_skip_hashed_batches(skip_hashed_batches);
+
+            let res = process.run(0, &RetryBudget::default()).await;
+            assert!(res.is_ok());
+            assert_eq!(
+                process.hashing_provider.stored_hash_reads(),
+                expected_stored_hash_reads
+            );
+            assert_eq!(process.hashing_provider.submissions(), 0);
+        }
+    }
+
     #[tokio::test]
     async fn should_return_false_if_batch_hash_avg_fees_is_not_available() {
         let process = setup();
@@ -335,13 +961,287 @@ mod tests {
 
     #[tokio::test]
     async fn should_return_true_if_batch_hash_avg_fees_is_available() {
-        let mut process = setup();
-
-        Arc::get_mut(&mut process.hashing_provider)
-            .unwrap()
-            .set_hash_batched_avg_fee([1; 8]);
+        let process = setup_with(MockHashingProvider::new().with_hash_batched_avg_fees([1; 8]));
 
         let res = process.is_batch_hash_avg_fees_available(0).await;
         assert!(res.unwrap());
     }
+
+    #[tokio::test]
+    async fn should_bound_concurrent_submissions() {
+        const BATCHES: u64 = 20;
+        const MAX_CONCURRENT_SUBMISSIONS: usize = 3;
+
+        let process = setup().with_max_concurrent_submissions(MAX_CONCURRENT_SUBMISSIONS);
+
+        let batch_timestamps = (0..BATCHES)
+            .map(|i| i * HOUR_SECS * HASH_BATCH_SIZE as u64)
+            .collect();
+        let res = process
+            .hash_and_store_avg_fees_onchain(batch_timestamps, &RetryBudget::default())
+            .await;
+        assert_eq!(res.unwrap_err(), MOCK_SUBMISSION_ERROR);
+
+        let provider = &process.hashing_provider;
+        assert_eq!(provider.submissions(), BATCHES as usize);
+        assert!(
+            provider.max_submissions_in_flight() <= MAX_CONCURRENT_SUBMISSIONS,
+            "Expected at most {} concurrent submissions, got {}",
+            MAX_CONCURRENT_SUBMISSIONS,
+            provider.max_submissions_in_flight()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_retry_transaction_status_until_found() {
+        let provider = Arc::new(MockHashingProvider::new());
+        provider.push_transaction_status(Err(transaction_not_found()));
+        provider.push_transaction_status(Err(transaction_not_found()));
+        provider.push_transaction_status(Ok(TransactionExecutionStatus::Succeeded));
+
+        let res = wait_for_transaction_status(
+            provider.clone(),
+            Felt::ONE,
+            TEST_RECEIPT_RETRY,
+            RetryBudget::default(),
+        )
+        .await;
+        assert_eq!(res.unwrap(), TransactionExecutionStatus::Succeeded);
+        assert_eq!(provider.status_lookups(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_transaction_status_on_other_errors() {
+        let provider = Arc::new(MockHashingProvider::new());
+        provider.push_transaction_status(Err(ProviderError::RateLimited));
+
+        let res = wait_for_transaction_status(
+            provider.clone(),
+            Felt::ONE,
+            TEST_RECEIPT_RETRY,
+            RetryBudget::default(),
+        )
+        .await;
+        assert!(res.is_err());
+        assert_eq!(provider.status_lookups(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_give_up_when_transaction_is_never_found() {
+        let provider = Arc::new(MockHashingProvider::new());
+
+        let res = wait_for_transaction_status(
+            provider.clone(),
+            Felt::ONE,
+            TEST_RECEIPT_RETRY,
+            RetryBudget::default(),
+        )
+        .await;
+        assert!(res.is_err());
+        assert_eq!(
+            provider.status_lookups(),
+            TEST_RECEIPT_RETRY.attempts as usize
+        );
+    }
+
+    #[tokio::test]
+    async fn should_stop_retrying_once_the_retry_budget_is_spent() {
+        let provider = Arc::new(MockHashingProvider::new());
+        let retry_budget = RetryBudget::new(2);
+
+        // The transaction is never found: the first operation spends the whole budget
+        let res = wait_for_transaction_status(
+            provider.clone(),
+            Felt::ONE,
+            TEST_RECEIPT_RETRY,
+            retry_budget.clone(),
+        )
+        .await;
+        assert!(res.is_err());
+        assert_eq!(provider.status_lookups(), 3);
+        assert_eq!(retry_budget.remaining(), 0);
+
+        // Later operations fail on their first error instead of retrying
+        for _ in 0..2 {
+            let res = wait_for_transaction_status(
+                provider.clone(),
+                Felt::ONE,
+                TEST_RECEIPT_RETRY,
+                retry_budget.clone(),
+            )
+            .await;
+            assert!(res.is_err());
+        }
+        assert_eq!(provider.status_lookups(), 5);
+    }
+
+    #[test]
+    fn retry_budget_is_shared_between_clones() {
+        let retry_budget = RetryBudget::new(1);
+        let clone = retry_budget.clone();
+
+        assert!(clone.try_spend());
+        assert!(!retry_budget.try_spend());
+        assert_eq!(retry_budget.remaining(), 0);
+    }
+
+    const WINDOW_SECS: u64 = HOUR_SECS * REQUIRED_AVG_FEES_LENGTH as u64;
+
+    /// A cursor in a fresh temporary directory, removed along with the cursor when the
+    /// directory is dropped.
+    fn temp_cursor() -> (BackfillCursor, TempDir) {
+        let dir = TempDir::new().unwrap();
+        (BackfillCursor::new(dir.path().join("backfill_cursor")), dir)
+    }
+
+    /// A provider for which every window is already hashed, each run reading the fees and the
+    /// batch hash only.
+    fn provider_with_hashed_windows() -> MockHashingProvider {
+        provider_with_fees().with_hash_batched_avg_fees([1; 8])
+    }
+
+    #[test]
+    fn backfill_cursor_round_trips() {
+        let (cursor, _dir) = temp_cursor();
+        assert_eq!(cursor.load().unwrap(), None);
+
+        cursor.record(WINDOW_SECS).unwrap();
+        cursor.record(2 * WINDOW_SECS).unwrap();
+        assert_eq!(cursor.load().unwrap(), Some(2 * WINDOW_SECS));
+    }
+
+    #[tokio::test]
+    async fn run_range_records_every_completed_window() {
+        let (cursor, _dir) = temp_cursor();
+        let process =
+            setup_with(provider_with_hashed_windows()).with_backfill_cursor(cursor.clone());
+
+        let res = process
+            .run_range(0, 3 * WINDOW_SECS, false, &RetryBudget::default())
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(process.hashing_provider.reads(), 6);
+        assert_eq!(cursor.load().unwrap(), Some(2 * WINDOW_SECS));
+    }
+
+    #[tokio::test]
+    async fn resumed_run_range_skips_completed_windows() {
+        let (cursor, _dir) = temp_cursor();
+        cursor.record(WINDOW_SECS).unwrap();
+        let process =
+            setup_with(provider_with_hashed_windows()).with_backfill_cursor(cursor.clone());
+
+        let res = process
+            .run_range(0, 3 * WINDOW_SECS, true, &RetryBudget::default())
+            .await;
+        assert!(res.is_ok());
+        // Only the last window is run
+        assert_eq!(process.hashing_provider.reads(), 2);
+        assert_eq!(cursor.load().unwrap(), Some(2 * WINDOW_SECS));
+
+        // Without resume, every window is run again
+        let res = process
+            .run_range(0, 3 * WINDOW_SECS, false, &RetryBudget::default())
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(process.hashing_provider.reads(), 8);
+    }
+
+    #[tokio::test]
+    async fn resumed_run_range_ignores_cursor_outside_range() {
+        let (cursor, _dir) = temp_cursor();
+        cursor.record(10 * WINDOW_SECS).unwrap();
+        let process =
+            setup_with(provider_with_hashed_windows()).with_backfill_cursor(cursor.clone());
+
+        let res = process
+            .run_range(0, 2 * WINDOW_SECS, true, &RetryBudget::default())
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(process.hashing_provider.reads(), 4);
+        assert_eq!(cursor.load().unwrap(), Some(WINDOW_SECS));
+    }
+
+    /// Stands in for the contract's hash function: the sum of the fees in every limb.
+    fn sum_hasher(avg_fees: &[f64]) -> StoredHash {
+        StoredHash([avg_fees.iter().sum::<f64>() as u32; 8])
+    }
+
+    #[tokio::test]
+    async fn verify_stored_hashes_reports_mismatches() {
+        // Each batch of 10 fees of 1.0 hashes to [10; 8]
+        let process = setup_with(provider_with_fees().with_hash_stored_avg_fees([11; 8]))
+            .with_fee_hasher(sum_hasher);
+
+        let mismatches = process
+            .verify_stored_hashes(0, 2 * WINDOW_SECS, &RetryBudget::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch {
+                    timestamp: 0,
+                    stored: StoredHash([11; 8]),
+                    computed: StoredHash([10; 8]),
+                },
+                Mismatch {
+                    timestamp: WINDOW_SECS,
+                    stored: StoredHash([11; 8]),
+                    computed: StoredHash([10; 8]),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_stored_hashes_accepts_matching_hashes() {
+        let process = setup_with(provider_with_fees().with_hash_stored_avg_fees([10; 8]))
+            .with_fee_hasher(sum_hasher);
+
+        let mismatches = process
+            .verify_stored_hashes(0, 2 * WINDOW_SECS, &RetryBudget::default())
+            .await
+            .unwrap();
+        assert!(mismatches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn verify_stored_hashes_skips_batches_without_stored_hash() {
+        let process = setup_with(provider_with_fees()).with_fee_hasher(sum_hasher);
+
+        let mismatches = process
+            .verify_stored_hashes(0, 2 * WINDOW_SECS, &RetryBudget::default())
+            .await
+            .unwrap();
+        assert!(mismatches.is_empty());
+        // Only the stored hashes were read, not the fees
+        assert_eq!(process.hashing_provider.reads(), 2);
+    }
+
+    #[tokio::test]
+    async fn verify_stored_hashes_defaults_to_contract_hash() {
+        let stored = StoredHash::of_avg_fees(&[1.0; 10]);
+        let process = setup_with(provider_with_fees().with_hash_stored_avg_fees(stored.0));
+
+        let mismatches = process
+            .verify_stored_hashes(0, 2 * WINDOW_SECS, &RetryBudget::default())
+            .await
+            .unwrap();
+        assert!(mismatches.is_empty());
+
+        let process = setup_with(provider_with_fees().with_hash_stored_avg_fees([10; 8]));
+        let mismatches = process
+            .verify_stored_hashes(0, WINDOW_SECS, &RetryBudget::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            mismatches,
+            vec![Mismatch {
+                timestamp: 0,
+                stored: StoredHash([10; 8]),
+                computed: stored,
+            }]
+        );
+    }
 }
@@ -1,25 +1,168 @@
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::queue::message_queue::Queue;
+use chrono::{DateTime, Utc};
 use eyre::Result;
 
 use super::jobs::Job;
 
+/// Priority of jobs whose settlement is due within the hour (or already past).
+pub const MAX_PRIORITY: u8 = 5;
+
+/// Maps the time left until a job's settlement (its `end_timestamp`) to a priority. Jobs
+/// settling sooner get a higher priority.
+pub const fn settlement_priority(end_timestamp: i64, now: i64) -> u8 {
+    const HOUR: i64 = 3600;
+    const DAY: i64 = 24 * HOUR;
+
+    let remaining = end_timestamp.saturating_sub(now);
+    if remaining <= HOUR {
+        MAX_PRIORITY
+    } else if remaining <= 6 * HOUR {
+        4
+    } else if remaining <= DAY {
+        3
+    } else if remaining <= 7 * DAY {
+        2
+    } else if remaining <= 30 * DAY {
+        1
+    } else {
+        0
+    }
+}
+
+/// Correlation info for a dispatched job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchReceipt {
+    pub job_id: String,
+    /// Id the queue assigned to the message, if the queue reports one.
+    pub message_id: Option<String>,
+    pub dispatched_at: DateTime<Utc>,
+}
+
+impl DispatchReceipt {
+    /// Receipt for a job dispatched now.
+    pub fn new(job_id: String, message_id: Option<String>) -> Self {
+        Self {
+            job_id,
+            message_id,
+            dispatched_at: Utc::now(),
+        }
+    }
+}
+
 pub struct JobDispatcher<Q: Queue> {
     queue: Arc<Q>,
 }
 
-impl<Q: Queue> JobDispatcher<Q> {
+impl<Q: Queue + Sync> JobDispatcher<Q> {
     pub const fn new(queue: Arc<Q>) -> Self {
         Self { queue }
     }
 
-    pub async fn dispatch_job(&self, job: Job) -> Result<()> {
+    pub async fn dispatch_job(&self, mut job: Job) -> Result<DispatchReceipt> {
+        if let Job::RequestProof(request) = &mut job
+            && request.priority.is_none()
+        {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+            request.priority = Some(settlement_priority(request.end_timestamp, now));
+        }
+
         let message_body = serde_json::to_string(&job)?;
-        self.queue
-            .send_message(message_body)
+        let message_id = self
+            .queue
+            .send_message_with_id(message_body)
             .await
             .map_err(|e| eyre::eyre!(e))?;
-        Ok(())
+        Ok(DispatchReceipt::new(job.job_id().to_string(), message_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::local_message_queue::LocalMessageQueue;
+    use crate::services::jobs::RequestProof;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_imminent_settlement_has_higher_priority() {
+        let soon = settlement_priority(NOW + 30 * 60, NOW);
+        let later = settlement_priority(NOW + 3 * 24 * 3600, NOW);
+        let far = settlement_priority(NOW + 90 * 24 * 3600, NOW);
+
+        assert_eq!(soon, MAX_PRIORITY);
+        assert!(soon > later);
+        assert!(later > far);
+        assert_eq!(far, 0);
+    }
+
+    #[test]
+    fn test_past_settlement_has_max_priority() {
+        assert_eq!(settlement_priority(NOW - 3600, NOW), MAX_PRIORITY);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_returns_receipt() {
+        let queue = Arc::new(LocalMessageQueue::new());
+        let dispatcher = JobDispatcher::new(queue.clone());
+
+        let before = Utc::now();
+        let receipt = dispatcher
+            .dispatch_job(Job::RequestProof(RequestProof {
+                job_id: "reserve_price".to_string(),
+                job_group_id: None,
+                start_timestamp: 0,
+                end_timestamp: 0,
+                priority: None,
+                tag: None,
+                deadline_ts: None,
+                settlement_timestamp: None,
+                timeout_secs: None,
+                at_block_hash: None,
+                ranges: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(receipt.job_id, "reserve_price");
+        assert!(receipt.dispatched_at >= before && receipt.dispatched_at <= Utc::now());
+
+        let messages = queue.receive_messages().await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(receipt.message_id.is_some());
+        assert_eq!(receipt.message_id, messages[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_sets_priority() {
+        let queue = Arc::new(LocalMessageQueue::new());
+        let dispatcher = JobDispatcher::new(queue.clone());
+
+        dispatcher
+            .dispatch_job(Job::RequestProof(RequestProof {
+                job_id: "twap".to_string(),
+                job_group_id: None,
+                start_timestamp: 0,
+                end_timestamp: 0,
+                priority: None,
+                tag: None,
+                deadline_ts: None,
+                settlement_timestamp: None,
+                timeout_secs: None,
+                at_block_hash: None,
+                ranges: None,
+            }))
+            .await
+            .unwrap();
+
+        let messages = queue.receive_messages().await.unwrap();
+        let job: Job = serde_json::from_str(&messages[0].body).unwrap();
+        match job {
+            Job::RequestProof(request) => assert_eq!(request.priority, Some(MAX_PRIORITY)),
+            _ => panic!("Expected a RequestProof job"),
+        }
     }
 }
@@ -1,5 +1,12 @@
-use risc0_zkvm::Receipt;
+use std::fmt;
+
+use crate::hashing::parse_block_hash;
+use crate::proof_composition::MetricStatus;
+use crate::time::{HOUR_SECS, ProofRangeError, ProofTimestampRanges};
+use eyre::{Result, eyre};
+use risc0_zkvm::{Digest, Receipt};
 use serde::{Deserialize, Serialize};
+use starknet::core::types::{BlockId, BlockTag, Felt};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestProof {
@@ -7,12 +14,308 @@ pub struct RequestProof {
     pub job_group_id: Option<String>,
     pub start_timestamp: i64,
     pub end_timestamp: i64,
+    /// Higher values are more urgent. Filled in by the dispatcher from the settlement time
+    /// when left unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Opaque client value, returned unchanged on the resulting [`ProofGenerated`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Unix timestamp after which the proof is no longer useful to the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_ts: Option<i64>,
+    /// Settlement time of the vault the job is proving for, which every window of the job's
+    /// group ends at. Returned unchanged on the resulting [`ProofGenerated`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settlement_timestamp: Option<i64>,
+    /// Seconds the proof may take, overriding the handler's default timeout up to its ceiling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Hash of the block fee reads for the job are pinned to, for reproducible proofs. Reads
+    /// are made against the latest block when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at_block_hash: Option<String>,
+    /// Windows of every metric of the job's group, as resolved by the dispatcher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranges: Option<ProofTimestampRanges>,
+}
+
+/// A validated job group id. Group ids key the jobs of a group, as `group:job`, so they are
+/// limited to ASCII letters, digits, `-`, `_` and `.`, which can't be confused with the
+/// separator.
+///
+/// Ids following the `{program_id}-{vault}-{settlement_ts}` convention, with the vault as a
+/// `0x` address, expose their parts through [`Self::components`]. Other ids, such as UUIDs,
+/// stay opaque.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JobGroupId(String);
+
+/// The parts of a [`JobGroupId`] following the `{program_id}-{vault}-{settlement_ts}`
+/// convention.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobGroupComponents {
+    pub program_id: String,
+    pub vault: String,
+    pub settlement_ts: i64,
+}
+
+/// Why a string isn't a valid [`JobGroupId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobGroupIdError {
+    Empty,
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for JobGroupIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "job_group_id must not be empty."),
+            Self::InvalidCharacter(c) => write!(
+                f,
+                "job_group_id must only contain ASCII letters, digits, '-', '_' and '.', found {:?}.",
+                c
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JobGroupIdError {}
+
+impl JobGroupId {
+    pub fn parse(id: &str) -> Result<Self, JobGroupIdError> {
+        if id.trim().is_empty() {
+            return Err(JobGroupIdError::Empty);
+        }
+        if let Some(c) = id
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        {
+            return Err(JobGroupIdError::InvalidCharacter(c));
+        }
+
+        Ok(Self(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The id's parts, if it follows the `{program_id}-{vault}-{settlement_ts}` convention.
+    pub fn components(&self) -> Option<JobGroupComponents> {
+        let mut parts = self.0.rsplitn(3, '-');
+        let settlement_ts = parts.next()?.parse::<i64>().ok()?;
+        let vault = parts.next()?;
+        let program_id = parts.next()?;
+
+        let is_address = vault
+            .strip_prefix("0x")
+            .is_some_and(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        if !is_address || program_id.is_empty() {
+            return None;
+        }
+
+        Some(JobGroupComponents {
+            program_id: program_id.to_string(),
+            vault: vault.to_string(),
+            settlement_ts,
+        })
+    }
+}
+
+impl fmt::Display for JobGroupId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for JobGroupId {
+    type Err = JobGroupIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl RequestProof {
+    /// Rejects time ranges no proof can be generated for, before any data is fetched for them:
+    /// those of the job's group, or the job's own window for every metric when unresolved.
+    pub fn check_range(&self) -> Result<(), ProofRangeError> {
+        let signed = |(start, end): (u64, u64)| {
+            (
+                i64::try_from(start).unwrap_or(i64::MAX),
+                i64::try_from(end).unwrap_or(i64::MAX),
+            )
+        };
+        let window = (self.start_timestamp, self.end_timestamp);
+        match self.ranges {
+            Some(ranges) => ProofTimestampRanges::try_new(
+                signed(ranges.twap),
+                signed(ranges.reserve_price),
+                signed(ranges.max_return),
+            ),
+            None => ProofTimestampRanges::try_new(window, window, window),
+        }
+        .map(|_| ())
+    }
+
+    /// Block fee reads for the job are made against: the one `at_block_hash` pins, or the
+    /// latest block. Fails when `at_block_hash` isn't a valid block hash.
+    pub fn block_id(&self) -> Result<BlockId, String> {
+        match &self.at_block_hash {
+            Some(at_block_hash) => parse_block_hash(at_block_hash),
+            None => Ok(BlockId::Tag(BlockTag::Latest)),
+        }
+    }
+
+    /// Windows the job's proof is computed over: those of its group, falling back to the job's
+    /// own window for every metric when the dispatcher didn't resolve them.
+    pub fn timestamp_ranges(&self) -> ProofTimestampRanges {
+        self.ranges.unwrap_or_else(|| {
+            let window = (
+                u64::try_from(self.start_timestamp).unwrap_or_default(),
+                u64::try_from(self.end_timestamp).unwrap_or_default(),
+            );
+            ProofTimestampRanges {
+                twap: window,
+                reserve_price: window,
+                max_return: window,
+            }
+        })
+    }
+
+    /// Key identifying this job among all in-flight jobs. Job ids are only unique within a group.
+    pub fn processing_key(&self) -> String {
+        match &self.job_group_id {
+            Some(job_group_id) => format!("{}:{}", job_group_id, self.job_id),
+            None => self.job_id.clone(),
+        }
+    }
+
+    /// Whether `id` names this job, either by its processing key or by its group, which names
+    /// every job of the group at once.
+    pub fn is_named_by(&self, id: &str) -> bool {
+        self.job_group_id.as_deref() == Some(id) || self.processing_key() == id
+    }
+}
+
+/// The fee window a [`RequestProof`] resolves to, logged as a single line when the job starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobSummary {
+    pub key: String,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    /// Number of hourly fees the window spans, i.e. the fee rows the proof is computed from.
+    pub fee_hours: u64,
+    pub priority: Option<u8>,
+    pub deadline_ts: Option<i64>,
+}
+
+impl fmt::Display for JobSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "job={} window=[{}, {}] fee_hours={}",
+            self.key, self.start_timestamp, self.end_timestamp, self.fee_hours
+        )?;
+        if let Some(priority) = self.priority {
+            write!(f, " priority={}", priority)?;
+        }
+        if let Some(deadline_ts) = self.deadline_ts {
+            write!(f, " deadline={}", deadline_ts)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn describe_job(job: &RequestProof) -> JobSummary {
+    let span = job.end_timestamp.saturating_sub(job.start_timestamp).max(0) as u64;
+
+    JobSummary {
+        key: job.processing_key(),
+        start_timestamp: job.start_timestamp,
+        end_timestamp: job.end_timestamp,
+        fee_hours: span.div_ceil(HOUR_SECS),
+        priority: job.priority,
+        deadline_ts: job.deadline_ts,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofGenerated {
     pub job_id: String,
     pub receipt: Receipt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// How each metric's sub-proof was verified, when the provider reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metric_status: Option<MetricStatus>,
+    /// Settlement time from the [`RequestProof`], for the on-chain submission.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settlement_timestamp: Option<i64>,
+}
+
+// Splits 32 big-endian bytes into the low and high felts of a Cairo u256
+fn u256_felts(bytes: &[u8]) -> [Felt; 2] {
+    let (high, low) = bytes.split_at(16);
+    [
+        Felt::from_bytes_be_slice(low),
+        Felt::from_bytes_be_slice(high),
+    ]
+}
+
+impl ProofGenerated {
+    /// Calldata of `verify_mmr_proof(image_id: u256, seal: Span<u256>, journal: Span<u8>)` for
+    /// this proof, as passed to [`crate::response_handler::StarknetAccount::verify_mmr_proof`]:
+    /// the image id, the Groth16 seal in 32-byte big-endian words and the journal bytes, each
+    /// span prefixed with its length. Fails for receipts without a Groth16 seal, such as fake
+    /// ones, which the verifier can't check.
+    pub fn to_verify_calldata(&self, image_id: [u32; 8]) -> Result<Vec<Felt>> {
+        let seal = &self
+            .receipt
+            .inner
+            .groth16()
+            .map_err(|e| {
+                eyre!(
+                    "Receipt of job {} is not a Groth16 receipt: {}",
+                    self.job_id,
+                    e
+                )
+            })?
+            .seal;
+        if seal.len() % 32 != 0 {
+            return Err(eyre!(
+                "Groth16 seal of job {} is {} bytes, not a whole number of 32-byte words",
+                self.job_id,
+                seal.len()
+            ));
+        }
+        let journal = &self.receipt.journal.bytes;
+
+        let mut calldata = Vec::with_capacity(4 + seal.len() / 16 + journal.len());
+        calldata.extend(u256_felts(Digest::from(image_id).as_bytes()));
+        calldata.push(Felt::from(seal.len() / 32));
+        calldata.extend(seal.chunks(32).flat_map(u256_felts));
+        calldata.push(Felt::from(journal.len()));
+        calldata.extend(journal.iter().map(|&byte| Felt::from(byte)));
+        Ok(calldata)
+    }
+}
+
+/// A job given up on, as forwarded to the dead-letter queue: its proof generation failed too many
+/// times, or its range can never be proven, in which case `failures` is zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedProof {
+    pub job: RequestProof,
+    pub failures: u64,
+    pub last_error: String,
+}
+
+/// Asks the proof handlers to drop the jobs named by `cancel_job_id`, a group id or a
+/// processing key, whether they are still queued or already being proven.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CancelProof {
+    pub cancel_job_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,4 +323,315 @@ pub struct ProofGenerated {
 pub enum Job {
     RequestProof(RequestProof),
     ProofGenerated(Box<ProofGenerated>),
+    FailedProof(FailedProof),
+    CancelProof(CancelProof),
+}
+
+impl Job {
+    pub fn job_id(&self) -> &str {
+        match self {
+            Self::RequestProof(request) => &request.job_id,
+            Self::ProofGenerated(proof) => &proof.job_id,
+            Self::FailedProof(failed) => &failed.job.job_id,
+            Self::CancelProof(cancel) => &cancel.cancel_job_id,
+        }
+    }
+}
+
+/// A message that could not be parsed as a [`Job`], as forwarded to the dead-letter queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidMessage {
+    pub body: String,
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::{FakeReceipt, Groth16Receipt, InnerReceipt, MaybePruned};
+
+    fn proof_generated(inner: InnerReceipt, journal: Vec<u8>) -> ProofGenerated {
+        ProofGenerated {
+            job_id: "twap".to_string(),
+            receipt: Receipt::new(inner, journal),
+            tag: None,
+            metric_status: None,
+            settlement_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_to_verify_calldata_rejects_non_groth16_receipt() {
+        let fake_receipt = FakeReceipt::new(MaybePruned::Pruned(Digest::ZERO));
+        let proof = proof_generated(InnerReceipt::Fake(fake_receipt), vec![1, 2]);
+
+        let err = proof.to_verify_calldata([0; 8]).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Receipt of job twap is not a Groth16 receipt")
+        );
+    }
+
+    #[test]
+    fn test_to_verify_calldata_encodes_groth16_receipt() {
+        let mut seal = vec![0; 256];
+        seal[31] = 1;
+        seal[15] = 2;
+        let groth16 = Groth16Receipt::new(seal, MaybePruned::Pruned(Digest::ZERO), Digest::ZERO);
+        let proof = proof_generated(InnerReceipt::Groth16(groth16), vec![7, 8, 9]);
+        let image_id = [1, 0, 0, 0, 0, 0, 0, 0];
+
+        let calldata = proof.to_verify_calldata(image_id).unwrap();
+
+        // Image id, seal length and 8 words, journal length and 3 bytes
+        assert_eq!(calldata.len(), 2 + 1 + 8 * 2 + 1 + 3);
+        assert_eq!(
+            &calldata[..2],
+            u256_felts(Digest::from(image_id).as_bytes())
+        );
+        assert_eq!(calldata[2], Felt::from(8));
+        assert_eq!(&calldata[3..5], [Felt::ONE, Felt::TWO]);
+        assert_eq!(calldata[19], Felt::from(3));
+        assert_eq!(
+            &calldata[20..],
+            [Felt::from(7), Felt::from(8), Felt::from(9)]
+        );
+    }
+
+    #[test]
+    fn test_to_verify_calldata_rejects_partial_seal_word() {
+        let groth16 =
+            Groth16Receipt::new(vec![0; 33], MaybePruned::Pruned(Digest::ZERO), Digest::ZERO);
+        let proof = proof_generated(InnerReceipt::Groth16(groth16), vec![]);
+
+        assert!(proof.to_verify_calldata([0; 8]).is_err());
+    }
+
+    fn request(start_timestamp: i64, end_timestamp: i64) -> RequestProof {
+        RequestProof {
+            job_id: "twap".to_string(),
+            job_group_id: Some("group".to_string()),
+            start_timestamp,
+            end_timestamp,
+            priority: Some(2),
+            tag: None,
+            deadline_ts: None,
+            settlement_timestamp: None,
+            timeout_secs: None,
+            at_block_hash: None,
+            ranges: None,
+        }
+    }
+
+    #[test]
+    fn test_describe_job_counts_fee_hours() {
+        let start = 1_734_843_600;
+        let summary = describe_job(&request(start, start + 720 * HOUR_SECS as i64));
+
+        assert_eq!(summary.key, "group:twap");
+        assert_eq!(
+            summary.fee_hours,
+            (summary.end_timestamp - summary.start_timestamp) as u64 / HOUR_SECS
+        );
+        assert_eq!(summary.fee_hours, 720);
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "job=group:twap window=[{}, {}] fee_hours=720 priority=2",
+                start,
+                start + 720 * 3600
+            )
+        );
+    }
+
+    #[test]
+    fn test_describe_job_rounds_partial_hours_up() {
+        assert_eq!(describe_job(&request(0, 90 * 60)).fee_hours, 2);
+        assert_eq!(describe_job(&request(100, 0)).fee_hours, 0);
+    }
+
+    #[test]
+    fn test_timestamp_ranges_fall_back_to_the_job_window() {
+        let job = request(3600, 7200);
+        assert_eq!(
+            job.timestamp_ranges(),
+            ProofTimestampRanges {
+                twap: (3600, 7200),
+                reserve_price: (3600, 7200),
+                max_return: (3600, 7200),
+            }
+        );
+
+        let ranges = ProofTimestampRanges {
+            twap: (3600, 7200),
+            reserve_price: (0, 7200),
+            max_return: (1800, 7200),
+        };
+        let job = RequestProof {
+            ranges: Some(ranges),
+            ..job
+        };
+        assert_eq!(job.timestamp_ranges(), ranges);
+    }
+
+    #[test]
+    fn test_failed_proof_round_trip() {
+        let failed = Job::FailedProof(FailedProof {
+            job: request(0, 3600),
+            failures: 3,
+            last_error: "Proof generation timed out".to_string(),
+        });
+
+        let json = serde_json::to_string(&failed).unwrap();
+        match serde_json::from_str(&json).unwrap() {
+            Job::FailedProof(parsed) => {
+                assert_eq!(parsed.job.processing_key(), "group:twap");
+                assert_eq!(parsed.failures, 3);
+                assert_eq!(parsed.last_error, "Proof generation timed out");
+            }
+            other => panic!("Expected FailedProof job, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_job_group_id_accepts_valid_formats() {
+        for id in [
+            "PITCHLAKE_V1-0x1234abcd-1734847200",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "test-group-123",
+            "group.v2_a",
+        ] {
+            assert_eq!(JobGroupId::parse(id).unwrap().as_str(), id);
+        }
+    }
+
+    #[test]
+    fn test_job_group_id_rejects_invalid_formats() {
+        assert_eq!(JobGroupId::parse(""), Err(JobGroupIdError::Empty));
+        assert_eq!(JobGroupId::parse(" \t"), Err(JobGroupIdError::Empty));
+        assert_eq!(
+            JobGroupId::parse("group:twap"),
+            Err(JobGroupIdError::InvalidCharacter(':'))
+        );
+        assert_eq!(
+            JobGroupId::parse("my group"),
+            Err(JobGroupIdError::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            "vault/1".parse::<JobGroupId>(),
+            Err(JobGroupIdError::InvalidCharacter('/'))
+        );
+    }
+
+    #[test]
+    fn test_job_group_id_components() {
+        let id = JobGroupId::parse("PITCHLAKE_V1-0x1234abcd-1734847200").unwrap();
+        assert_eq!(
+            id.components(),
+            Some(JobGroupComponents {
+                program_id: "PITCHLAKE_V1".to_string(),
+                vault: "0x1234abcd".to_string(),
+                settlement_ts: 1_734_847_200,
+            })
+        );
+
+        // Only the last two parts are split off, the program id may contain dashes
+        let components = JobGroupId::parse("pitch-lake-0xAB-5")
+            .unwrap()
+            .components()
+            .unwrap();
+        assert_eq!(components.program_id, "pitch-lake");
+        assert_eq!(components.vault, "0xAB");
+        assert_eq!(components.settlement_ts, 5);
+
+        for opaque in [
+            "550e8400-e29b-41d4-a716-446655440000",
+            "test-group-123",
+            "PITCHLAKE_V1-0x-1734847200",
+            "PITCHLAKE_V1-0x12zz-1734847200",
+            "-0x1234-1734847200",
+            "PITCHLAKE_V1-0x1234-later",
+            "0x1234-1734847200",
+        ] {
+            assert_eq!(
+                JobGroupId::parse(opaque).unwrap().components(),
+                None,
+                "{}",
+                opaque
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancel_proof_round_trip() {
+        let json = serde_json::to_string(&Job::CancelProof(CancelProof {
+            cancel_job_id: "group".to_string(),
+        }))
+        .unwrap();
+        assert_eq!(json, r#"{"cancel_job_id":"group"}"#);
+
+        match serde_json::from_str(&json).unwrap() {
+            Job::CancelProof(parsed) => assert_eq!(parsed.cancel_job_id, "group"),
+            other => panic!("Expected CancelProof job, got {:?}", other),
+        }
+
+        // A request missing its range isn't mistaken for a cancellation
+        assert!(serde_json::from_str::<Job>(r#"{"job_id":"twap"}"#).is_err());
+    }
+
+    #[test]
+    fn test_is_named_by_group_or_processing_key() {
+        let job = request(0, 3600);
+
+        assert!(job.is_named_by("group"));
+        assert!(job.is_named_by("group:twap"));
+        assert!(!job.is_named_by("twap"));
+        assert!(!job.is_named_by("other"));
+    }
+
+    #[test]
+    fn test_check_range_accepts_increasing_range() {
+        assert_eq!(request(0, 1).check_range(), Ok(()));
+        assert_eq!(request(1_734_843_600, 1_734_847_200).check_range(), Ok(()));
+    }
+
+    #[test]
+    fn test_check_range_rejects_invalid_window() {
+        assert_eq!(
+            request(-3600, 3600).check_range(),
+            Err(ProofRangeError::NegativeTimestamp {
+                component: "twap",
+                timestamp: -3600,
+            })
+        );
+        assert_eq!(
+            request(7200, 3600).check_range(),
+            Err(ProofRangeError::Empty {
+                component: "twap",
+                start_timestamp: 7200,
+                end_timestamp: 3600,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_range_names_the_invalid_component() {
+        let job = RequestProof {
+            ranges: Some(ProofTimestampRanges {
+                twap: (3600, 7200),
+                reserve_price: (0, 7200),
+                max_return: (7200, 7200),
+            }),
+            ..request(3600, 7200)
+        };
+
+        assert_eq!(
+            job.check_range(),
+            Err(ProofRangeError::Empty {
+                component: "max_return",
+                start_timestamp: 7200,
+                end_timestamp: 7200,
+            })
+        );
+    }
 }
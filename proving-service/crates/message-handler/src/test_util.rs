@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use starknet::{
+    core::types::{Felt, InvokeTransactionResult, StarknetError, TransactionExecutionStatus},
+    providers::{JsonRpcClient, ProviderError, Url, jsonrpc::HttpTransport},
+};
+
+use crate::hashing::{HashingProviderTrait, StoredHash};
+use crate::services::hashing_service::RetryBudget;
+
+/// Error the mock's submissions fail with unless told otherwise.
+pub const MOCK_SUBMISSION_ERROR: &str = "Mock submission";
+
+/// [`HashingProviderTrait`] answering from configured values instead of a node, counting the
+/// calls made to it.
+pub struct MockHashingProvider {
+    provider: JsonRpcClient<HttpTransport>,
+    fossil_light_client_address: Felt,
+    hash_storage_address: Felt,
+    avg_fees: Vec<f64>,
+    queued_avg_fees: Mutex<VecDeque<Vec<f64>>>,
+    hash_stored_avg_fees: StoredHash,
+    hash_batched_avg_fees: StoredHash,
+    submission_result: Result<InvokeTransactionResult, String>,
+    submission_delay: Duration,
+    transaction_statuses: Mutex<VecDeque<Result<TransactionExecutionStatus, ProviderError>>>,
+    reads: AtomicUsize,
+    stored_hash_reads: AtomicUsize,
+    submissions: AtomicUsize,
+    status_lookups: AtomicUsize,
+    submissions_in_flight: AtomicUsize,
+    max_submissions_in_flight: AtomicUsize,
+}
+
+impl MockHashingProvider {
+    /// A provider with no fees and no stored hashes, whose submissions fail with
+    /// [`MOCK_SUBMISSION_ERROR`] and whose transactions are never found.
+    pub fn new() -> Self {
+        Self {
+            provider: JsonRpcClient::new(HttpTransport::new(
+                Url::parse("http://localhost:5050").expect("valid URL"),
+            )),
+            fossil_light_client_address: Felt::ZERO,
+            hash_storage_address: Felt::ZERO,
+            avg_fees: vec![],
+            queued_avg_fees: Mutex::new(VecDeque::new()),
+            hash_stored_avg_fees: StoredHash::default(),
+            hash_batched_avg_fees: StoredHash::default(),
+            submission_result: Err(MOCK_SUBMISSION_ERROR.to_string()),
+            submission_delay: Duration::from_millis(10),
+            transaction_statuses: Mutex::new(VecDeque::new()),
+            reads: AtomicUsize::new(0),
+            stored_hash_reads: AtomicUsize::new(0),
+            submissions: AtomicUsize::new(0),
+            status_lookups: AtomicUsize::new(0),
+            submissions_in_flight: AtomicUsize::new(0),
+            max_submissions_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    pub const fn with_addresses(
+        mut self,
+        fossil_light_client_address: Felt,
+        hash_storage_address: Felt,
+    ) -> Self {
+        self.fossil_light_client_address = fossil_light_client_address;
+        self.hash_storage_address = hash_storage_address;
+        self
+    }
+
+    /// Fees returned for any range.
+    pub fn with_avg_fees(mut self, avg_fees: Vec<f64>) -> Self {
+        self.avg_fees = avg_fees;
+        self
+    }
+
+    /// Queues the fees returned by the next read, as a light client catching up would. Once the
+    /// queue is empty, reads return the fees set with [`Self::with_avg_fees`].
+    pub fn push_avg_fees(&self, avg_fees: Vec<f64>) {
+        self.queued_avg_fees.lock().unwrap().push_back(avg_fees);
+    }
+
+    /// Hash returned as stored for any timestamp.
+    pub const fn with_hash_stored_avg_fees(mut self, hash: [u32; 8]) -> Self {
+        self.hash_stored_avg_fees = StoredHash(hash);
+        self
+    }
+
+    /// Hash returned as stored for any batch.
+    pub const fn with_hash_batched_avg_fees(mut self, hash: [u32; 8]) -> Self {
+        self.hash_batched_avg_fees = StoredHash(hash);
+        self
+    }
+
+    /// Result every submission returns.
+    pub fn with_submission_result(
+        mut self,
+        submission_result: Result<InvokeTransactionResult, String>,
+    ) -> Self {
+        self.submission_result = submission_result;
+        self
+    }
+
+    /// Time each submission takes, so concurrent submissions overlap.
+    pub const fn with_submission_delay(mut self, submission_delay: Duration) -> Self {
+        self.submission_delay = submission_delay;
+        self
+    }
+
+    pub fn with_transaction_status(
+        self,
+        status: Result<TransactionExecutionStatus, ProviderError>,
+    ) -> Self {
+        self.push_transaction_status(status);
+        self
+    }
+
+    /// Queues the result of the next transaction status lookup. Once the queue is empty, lookups
+    /// fail as if the transaction wasn't found.
+    pub fn push_transaction_status(
+        &self,
+        status: Result<TransactionExecutionStatus, ProviderError>,
+    ) {
+        self.transaction_statuses.lock().unwrap().push_back(status);
+    }
+
+    /// Number of reads of fees and stored hashes.
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::SeqCst)
+    }
+
+    /// Number of reads of per-timestamp stored hashes.
+    pub fn stored_hash_reads(&self) -> usize {
+        self.stored_hash_reads.load(Ordering::SeqCst)
+    }
+
+    /// Number of hashes submitted, single and batched.
+    pub fn submissions(&self) -> usize {
+        self.submissions.load(Ordering::SeqCst)
+    }
+
+    pub fn status_lookups(&self) -> usize {
+        self.status_lookups.load(Ordering::SeqCst)
+    }
+
+    /// Most submissions seen in flight at once.
+    pub fn max_submissions_in_flight(&self) -> usize {
+        self.max_submissions_in_flight.load(Ordering::SeqCst)
+    }
+
+    async fn submit(&self) -> Result<InvokeTransactionResult, String> {
+        self.submissions.fetch_add(1, Ordering::SeqCst);
+        let in_flight = self.submissions_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_submissions_in_flight
+            .fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(self.submission_delay).await;
+        self.submissions_in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.submission_result.clone()
+    }
+}
+
+impl Default for MockHashingProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned by a node for a transaction it doesn't know (yet).
+pub const fn transaction_not_found() -> ProviderError {
+    ProviderError::StarknetError(StarknetError::TransactionHashNotFound)
+}
+
+#[async_trait]
+impl HashingProviderTrait for MockHashingProvider {
+    fn get_provider(&self) -> &JsonRpcClient<HttpTransport> {
+        &self.provider
+    }
+
+    fn get_fossil_light_client_address(&self) -> &Felt {
+        &self.fossil_light_client_address
+    }
+
+    fn get_hash_storage_address(&self) -> &Felt {
+        &self.hash_storage_address
+    }
+
+    async fn get_avg_fees_in_range(
+        &self,
+        _start_timestamp: u64,
+        _end_timestamp: u64,
+        _retry_budget: &RetryBudget,
+    ) -> Result<Vec<f64>, ProviderError> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        let queued = self.queued_avg_fees.lock().unwrap().pop_front();
+        Ok(queued.unwrap_or_else(|| self.avg_fees.clone()))
+    }
+
+    async fn get_hash_stored_avg_fees(&self, _timestamp: u64) -> Result<StoredHash, ProviderError> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.stored_hash_reads.fetch_add(1, Ordering::SeqCst);
+        Ok(self.hash_stored_avg_fees)
+    }
+
+    async fn get_hash_batched_avg_fees(
+        &self,
+        _start_timestamp: u64,
+    ) -> Result<StoredHash, ProviderError> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        Ok(self.hash_batched_avg_fees)
+    }
+
+    async fn hash_avg_fees_and_store(
+        &self,
+        _start_timestamp: u64,
+    ) -> Result<InvokeTransactionResult, String> {
+        self.submit().await
+    }
+
+    async fn hash_batched_avg_fees(
+        &self,
+        _start_timestamp: u64,
+    ) -> Result<InvokeTransactionResult, String> {
+        self.submit().await
+    }
+
+    async fn get_transaction_status(
+        &self,
+        _transaction_hash: Felt,
+    ) -> Result<TransactionExecutionStatus, ProviderError> {
+        self.status_lookups.fetch_add(1, Ordering::SeqCst);
+        self.transaction_statuses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or(Err(transaction_not_found()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_returns_configured_values() {
+        let provider = MockHashingProvider::new()
+            .with_addresses(Felt::ONE, Felt::TWO)
+            .with_avg_fees(vec![1.0, 2.0])
+            .with_hash_stored_avg_fees([1; 8])
+            .with_hash_batched_avg_fees([2; 8]);
+
+        assert_eq!(provider.get_fossil_light_client_address(), &Felt::ONE);
+        assert_eq!(provider.get_hash_storage_address(), &Felt::TWO);
+        assert_eq!(
+            provider
+                .get_avg_fees_in_range(0, 3600, &RetryBudget::default())
+                .await
+                .unwrap(),
+            vec![1.0, 2.0]
+        );
+        assert_eq!(
+            provider.get_hash_stored_avg_fees(0).await.unwrap(),
+            StoredHash([1; 8])
+        );
+        assert_eq!(
+            provider.get_hash_batched_avg_fees(0).await.unwrap(),
+            StoredHash([2; 8])
+        );
+        assert_eq!(provider.reads(), 3);
+        assert_eq!(provider.stored_hash_reads(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_submissions_return_configured_result() {
+        let provider = MockHashingProvider::new();
+        assert_eq!(
+            provider.hash_avg_fees_and_store(0).await.unwrap_err(),
+            MOCK_SUBMISSION_ERROR
+        );
+
+        let provider = MockHashingProvider::new()
+            .with_submission_result(Ok(InvokeTransactionResult {
+                transaction_hash: Felt::THREE,
+            }))
+            .with_submission_delay(Duration::ZERO);
+        assert_eq!(
+            provider
+                .hash_batched_avg_fees(0)
+                .await
+                .unwrap()
+                .transaction_hash,
+            Felt::THREE
+        );
+        assert_eq!(provider.submissions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_transaction_statuses_are_returned_in_order() {
+        let provider = MockHashingProvider::new()
+            .with_transaction_status(Ok(TransactionExecutionStatus::Reverted));
+        provider.push_transaction_status(Ok(TransactionExecutionStatus::Succeeded));
+
+        assert_eq!(
+            provider.get_transaction_status(Felt::ONE).await.unwrap(),
+            TransactionExecutionStatus::Reverted
+        );
+        assert_eq!(
+            provider.get_transaction_status(Felt::ONE).await.unwrap(),
+            TransactionExecutionStatus::Succeeded
+        );
+        assert!(provider.get_transaction_status(Felt::ONE).await.is_err());
+        assert_eq!(provider.status_lookups(), 3);
+    }
+}
@@ -0,0 +1,430 @@
+/// Number of seconds in an hour, the interval the hourly average fees are stored at.
+pub const HOUR_SECS: u64 = 3600;
+
+/// Index of `ts` in a series of `interval_secs` spaced points starting at `base_ts`.
+///
+/// Returns `None` if `ts` is before `base_ts`, isn't aligned to the interval, or the interval
+/// is zero.
+pub fn hour_index(base_ts: u64, ts: u64, interval_secs: u64) -> Option<usize> {
+    if interval_secs == 0 {
+        return None;
+    }
+
+    let offset = ts.checked_sub(base_ts)?;
+    if offset % interval_secs != 0 {
+        return None;
+    }
+
+    usize::try_from(offset / interval_secs).ok()
+}
+
+/// Number of `interval_secs` spaced fee points from `start` up to and including `end`. A range
+/// whose end isn't aligned to the interval only counts the points before it.
+///
+/// Returns 0 if `end` is before `start` or the interval is zero.
+pub fn expected_fee_points(start: u64, end: u64, interval_secs: u64) -> usize {
+    if interval_secs == 0 || end < start {
+        return 0;
+    }
+
+    usize::try_from((end - start) / interval_secs + 1).unwrap_or(usize::MAX)
+}
+
+/// Timestamp of the last of `points` fee points spaced `interval_secs` apart from `start`, the
+/// inverse of [`expected_fee_points`]. `None` for no points, or if the end would overflow.
+pub fn last_fee_point(start: u64, points: usize, interval_secs: u64) -> Option<u64> {
+    let steps = u64::try_from(points.checked_sub(1)?).ok()?;
+    start.checked_add(interval_secs.checked_mul(steps)?)
+}
+
+use serde::{Deserialize, Serialize};
+
+/// The `(start, end)` timestamps of the windows a proof's metrics are computed over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofTimestampRanges {
+    pub twap: (u64, u64),
+    pub reserve_price: (u64, u64),
+    pub max_return: (u64, u64),
+}
+
+impl ProofTimestampRanges {
+    /// Ranges of the given `(start, end)` windows, rejecting any window with a negative
+    /// timestamp or that doesn't start before it ends.
+    pub fn try_new(
+        twap: (i64, i64),
+        reserve_price: (i64, i64),
+        max_return: (i64, i64),
+    ) -> Result<Self, ProofRangeError> {
+        Ok(Self {
+            twap: check_window("twap", twap)?,
+            reserve_price: check_window("reserve_price", reserve_price)?,
+            max_return: check_window("max_return", max_return)?,
+        })
+    }
+
+    /// The span covering every window, from the earliest start to the latest end.
+    pub fn overall(&self) -> (u64, u64) {
+        let windows = [self.twap, self.reserve_price, self.max_return];
+        let start = windows
+            .iter()
+            .map(|(start, _)| *start)
+            .min()
+            .unwrap_or_default();
+        let end = windows
+            .iter()
+            .map(|(_, end)| *end)
+            .max()
+            .unwrap_or_default();
+        (start, end)
+    }
+}
+
+/// Why the window of a [`ProofTimestampRanges`] component can't be proven.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofRangeError {
+    /// A timestamp is before the Unix epoch.
+    NegativeTimestamp {
+        component: &'static str,
+        timestamp: i64,
+    },
+    /// The window doesn't start before it ends.
+    Empty {
+        component: &'static str,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    },
+}
+
+impl std::fmt::Display for ProofRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NegativeTimestamp {
+                component,
+                timestamp,
+            } => write!(
+                f,
+                "Invalid {} timestamp {}: must not be negative",
+                component, timestamp
+            ),
+            Self::Empty {
+                component,
+                start_timestamp,
+                end_timestamp,
+            } => write!(
+                f,
+                "Invalid {} range: start {} is not before end {}",
+                component, start_timestamp, end_timestamp
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProofRangeError {}
+
+/// Checks `component`'s `(start, end)` window, returning it unsigned.
+fn check_window(
+    component: &'static str,
+    (start, end): (i64, i64),
+) -> Result<(u64, u64), ProofRangeError> {
+    for timestamp in [start, end] {
+        if timestamp < 0 {
+            return Err(ProofRangeError::NegativeTimestamp {
+                component,
+                timestamp,
+            });
+        }
+    }
+    if start >= end {
+        return Err(ProofRangeError::Empty {
+            component,
+            start_timestamp: start,
+            end_timestamp: end,
+        });
+    }
+
+    Ok((start as u64, end as u64))
+}
+
+/// Average time between Ethereum blocks, used to estimate how many blocks a range should hold.
+pub const ETHEREUM_BLOCK_TIME_SECS: u64 = 12;
+
+/// A range holding fewer than this percentage of its expected blocks is reported as sparse.
+pub const MIN_BLOCK_DENSITY_PERCENT: u64 = 50;
+
+/// Fewer blocks were found in a time range than its length suggests, hinting at missing data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseBlocksWarning {
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub expected: u64,
+    pub actual: usize,
+}
+
+impl std::fmt::Display for SparseBlocksWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Found {} blocks between {} and {}, expected about {}",
+            self.actual, self.start_timestamp, self.end_timestamp, self.expected
+        )
+    }
+}
+
+/// Checks that `count` blocks are plausible for the range, given one block every
+/// `expected_block_time_secs` on average. Returns a warning if fewer than
+/// [`MIN_BLOCK_DENSITY_PERCENT`] of the expected blocks are present, and `None` for an empty
+/// range or a zero block time.
+pub fn check_block_density(
+    count: usize,
+    start_timestamp: i64,
+    end_timestamp: i64,
+    expected_block_time_secs: u64,
+) -> Option<SparseBlocksWarning> {
+    if expected_block_time_secs == 0 || end_timestamp <= start_timestamp {
+        return None;
+    }
+
+    let expected = end_timestamp.abs_diff(start_timestamp) / expected_block_time_secs;
+    if (count as u64).saturating_mul(100) >= expected.saturating_mul(MIN_BLOCK_DENSITY_PERCENT) {
+        return None;
+    }
+
+    Some(SparseBlocksWarning {
+        start_timestamp,
+        end_timestamp,
+        expected,
+        actual: count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: u64 = 1_734_843_600;
+
+    #[test]
+    fn test_base_timestamp_is_index_zero() {
+        assert_eq!(hour_index(BASE, BASE, HOUR_SECS), Some(0));
+    }
+
+    #[test]
+    fn test_aligned_timestamps() {
+        assert_eq!(hour_index(BASE, BASE + HOUR_SECS, HOUR_SECS), Some(1));
+        assert_eq!(
+            hour_index(BASE, BASE + 2159 * HOUR_SECS, HOUR_SECS),
+            Some(2159)
+        );
+        assert_eq!(
+            hour_index(BASE, BASE + 5759 * HOUR_SECS, HOUR_SECS),
+            Some(5759)
+        );
+    }
+
+    #[test]
+    fn test_other_intervals() {
+        // e.g. stepping through batches of 10 hours
+        let batch_secs = 10 * HOUR_SECS;
+        assert_eq!(hour_index(BASE, BASE + 3 * batch_secs, batch_secs), Some(3));
+        assert_eq!(hour_index(0, 120, 60), Some(2));
+    }
+
+    #[test]
+    fn test_misaligned_timestamps() {
+        assert_eq!(hour_index(BASE, BASE + 1, HOUR_SECS), None);
+        assert_eq!(hour_index(BASE, BASE + HOUR_SECS - 1, HOUR_SECS), None);
+        assert_eq!(hour_index(BASE, BASE + HOUR_SECS + 1800, HOUR_SECS), None);
+    }
+
+    #[test]
+    fn test_timestamp_before_base() {
+        assert_eq!(hour_index(BASE, BASE - HOUR_SECS, HOUR_SECS), None);
+        assert_eq!(hour_index(BASE, 0, HOUR_SECS), None);
+    }
+
+    #[test]
+    fn test_zero_interval() {
+        assert_eq!(hour_index(BASE, BASE, 0), None);
+        assert_eq!(hour_index(BASE, BASE + HOUR_SECS, 0), None);
+    }
+
+    #[test]
+    fn test_dense_range_has_no_warning() {
+        let end = BASE as i64 + HOUR_SECS as i64;
+        assert_eq!(
+            check_block_density(300, BASE as i64, end, ETHEREUM_BLOCK_TIME_SECS),
+            None
+        );
+        // Exactly half the expected blocks is still dense enough
+        assert_eq!(
+            check_block_density(150, BASE as i64, end, ETHEREUM_BLOCK_TIME_SECS),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sparse_range_warns() {
+        let end = BASE as i64 + HOUR_SECS as i64;
+        let warning = check_block_density(149, BASE as i64, end, ETHEREUM_BLOCK_TIME_SECS);
+
+        assert_eq!(
+            warning,
+            Some(SparseBlocksWarning {
+                start_timestamp: BASE as i64,
+                end_timestamp: end,
+                expected: 300,
+                actual: 149,
+            })
+        );
+        assert_eq!(
+            warning.unwrap().to_string(),
+            format!(
+                "Found 149 blocks between {} and {}, expected about 300",
+                BASE, end
+            )
+        );
+    }
+
+    #[test]
+    fn test_empty_range() {
+        let start = BASE as i64;
+        // A range with no blocks at all is as sparse as it gets
+        assert!(check_block_density(0, start, start + 3600, ETHEREUM_BLOCK_TIME_SECS).is_some());
+        // A range too short to expect any block
+        assert_eq!(
+            check_block_density(0, start, start + 5, ETHEREUM_BLOCK_TIME_SECS),
+            None
+        );
+        // An empty or reversed range, or a disabled check
+        assert_eq!(
+            check_block_density(0, start, start, ETHEREUM_BLOCK_TIME_SECS),
+            None
+        );
+        assert_eq!(
+            check_block_density(0, start, start - 3600, ETHEREUM_BLOCK_TIME_SECS),
+            None
+        );
+        assert_eq!(check_block_density(0, start, start + 3600, 0), None);
+    }
+
+    #[test]
+    fn test_extreme_timestamps() {
+        assert_eq!(hour_index(0, u64::MAX, 1), usize::try_from(u64::MAX).ok());
+        assert_eq!(hour_index(u64::MAX, u64::MAX, HOUR_SECS), Some(0));
+    }
+
+    #[test]
+    fn test_expected_fee_points_exact_range() {
+        assert_eq!(expected_fee_points(BASE, BASE, HOUR_SECS), 1);
+        assert_eq!(
+            expected_fee_points(BASE, BASE + 9 * HOUR_SECS, HOUR_SECS),
+            10
+        );
+        assert_eq!(
+            expected_fee_points(BASE, BASE + 5759 * HOUR_SECS, HOUR_SECS),
+            5760
+        );
+    }
+
+    #[test]
+    fn test_expected_fee_points_off_by_one() {
+        // One second short of the next point doesn't count it, one past it doesn't add another
+        assert_eq!(
+            expected_fee_points(BASE, BASE + 10 * HOUR_SECS - 1, HOUR_SECS),
+            10
+        );
+        assert_eq!(
+            expected_fee_points(BASE, BASE + 10 * HOUR_SECS, HOUR_SECS),
+            11
+        );
+        assert_eq!(
+            expected_fee_points(BASE, BASE + 10 * HOUR_SECS + 1, HOUR_SECS),
+            11
+        );
+    }
+
+    #[test]
+    fn test_expected_fee_points_misaligned_or_invalid_range() {
+        assert_eq!(expected_fee_points(BASE, BASE + 1800, HOUR_SECS), 1);
+        assert_eq!(
+            expected_fee_points(BASE + 1800, BASE + 3 * HOUR_SECS, HOUR_SECS),
+            3
+        );
+        assert_eq!(expected_fee_points(BASE, BASE - 1, HOUR_SECS), 0);
+        assert_eq!(expected_fee_points(BASE, BASE + HOUR_SECS, 0), 0);
+    }
+
+    #[test]
+    fn test_proof_timestamp_ranges_overall_spans_every_window() {
+        let ranges = ProofTimestampRanges {
+            twap: (BASE + 2 * HOUR_SECS, BASE + 10 * HOUR_SECS),
+            reserve_price: (BASE, BASE + 9 * HOUR_SECS),
+            max_return: (BASE + HOUR_SECS, BASE + 10 * HOUR_SECS),
+        };
+
+        assert_eq!(ranges.overall(), (BASE, BASE + 10 * HOUR_SECS));
+    }
+
+    #[test]
+    fn test_proof_timestamp_ranges_try_new_accepts_valid_windows() {
+        assert_eq!(
+            ProofTimestampRanges::try_new((3600, 7200), (0, 7200), (1800, 7200)),
+            Ok(ProofTimestampRanges {
+                twap: (3600, 7200),
+                reserve_price: (0, 7200),
+                max_return: (1800, 7200),
+            })
+        );
+    }
+
+    #[test]
+    fn test_proof_timestamp_ranges_try_new_names_the_invalid_component() {
+        let valid = (3600, 7200);
+        for (component, ranges) in [
+            ("twap", [(-1, 7200), valid, valid]),
+            ("reserve_price", [valid, (3600, -1), valid]),
+            ("max_return", [valid, valid, (-3600, 0)]),
+        ] {
+            let err = ProofTimestampRanges::try_new(ranges[0], ranges[1], ranges[2]).unwrap_err();
+            assert!(matches!(
+                err,
+                ProofRangeError::NegativeTimestamp { component: c, .. } if c == component
+            ));
+        }
+
+        for (component, ranges) in [
+            ("twap", [(7200, 7200), valid, valid]),
+            ("reserve_price", [valid, (7200, 3600), valid]),
+            ("max_return", [valid, valid, (7200, 0)]),
+        ] {
+            let err = ProofTimestampRanges::try_new(ranges[0], ranges[1], ranges[2]).unwrap_err();
+            assert!(matches!(
+                err,
+                ProofRangeError::Empty { component: c, .. } if c == component
+            ));
+        }
+
+        assert_eq!(
+            ProofTimestampRanges::try_new(valid, (7200, 3600), valid)
+                .unwrap_err()
+                .to_string(),
+            "Invalid reserve_price range: start 7200 is not before end 3600"
+        );
+        assert_eq!(
+            ProofTimestampRanges::try_new(valid, valid, (-1, 7200))
+                .unwrap_err()
+                .to_string(),
+            "Invalid max_return timestamp -1: must not be negative"
+        );
+    }
+
+    #[test]
+    fn test_last_fee_point_inverts_expected_fee_points() {
+        for points in [1, 2, 10, 5760] {
+            let end = last_fee_point(BASE, points, HOUR_SECS).unwrap();
+            assert_eq!(expected_fee_points(BASE, end, HOUR_SECS), points);
+        }
+        assert_eq!(last_fee_point(BASE, 0, HOUR_SECS), None);
+        assert_eq!(last_fee_point(u64::MAX, 2, HOUR_SECS), None);
+    }
+}
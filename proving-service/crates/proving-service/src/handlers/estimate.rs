@@ -0,0 +1,140 @@
+use axum::{
+    extract::Json,
+    http::StatusCode,
+    response::{IntoResponse, Response as HttpResponse},
+};
+use message_handler::proof_composition::{BonsaiProofProvider, EstimateUnsupported, ProofProvider};
+use message_handler::time::ProofTimestampRanges;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::jobs::TimeRange;
+
+#[derive(Debug, Deserialize)]
+pub struct EstimateRequest {
+    twap: TimeRange,
+    reserve_price: TimeRange,
+    max_return: TimeRange,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EstimateError {
+    message: String,
+}
+
+fn error_response(status: StatusCode, message: String) -> HttpResponse {
+    (status, Json(EstimateError { message })).into_response()
+}
+
+fn to_window(range: &TimeRange) -> Result<(u64, u64), String> {
+    let (start, end) = range.window();
+    match (u64::try_from(start), u64::try_from(end)) {
+        (Ok(start), Ok(end)) => Ok((start, end)),
+        _ => Err(format!(
+            "Timestamps must not be negative, got {} to {}",
+            start, end
+        )),
+    }
+}
+
+impl EstimateRequest {
+    fn ranges(&self) -> Result<ProofTimestampRanges, String> {
+        Ok(ProofTimestampRanges {
+            twap: to_window(&self.twap)?,
+            reserve_price: to_window(&self.reserve_price)?,
+            max_return: to_window(&self.max_return)?,
+        })
+    }
+}
+
+/// Estimates the cost of proving the requested ranges without proving them, so integrators can
+/// size a request before committing to a Bonsai run.
+pub async fn estimate_proof(Json(request): Json<EstimateRequest>) -> HttpResponse {
+    let ranges = match request.ranges() {
+        Ok(ranges) => ranges,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
+    };
+    info!("Received estimate request for {:?}", ranges);
+
+    match BonsaiProofProvider::new().estimate(&ranges) {
+        Ok(estimate) => (StatusCode::OK, Json(estimate)).into_response(),
+        Err(e) if e.downcast_ref::<EstimateUnsupported>().is_some() => {
+            error_response(StatusCode::NOT_IMPLEMENTED, e.to_string())
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, header},
+        routing::post,
+    };
+    use serde_json::{Value, json};
+    use tower::ServiceExt;
+
+    const HOUR: i64 = 3600;
+
+    fn request_body(start: i64, end: i64) -> Value {
+        let range = json!({ "start_timestamp": start, "end_timestamp": end });
+        json!({ "twap": range, "reserve_price": range, "max_return": range })
+    }
+
+    async fn request_estimate(body: Value) -> (StatusCode, Value) {
+        let response = Router::new()
+            .route("/estimate", post(estimate_proof))
+            .oneshot(
+                Request::post("/estimate")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_estimate_scales_with_range_width() {
+        let (status, narrow) = request_estimate(request_body(0, 720 * HOUR)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, wide) = request_estimate(request_body(0, 2160 * HOUR)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert_eq!(narrow["fee_points"], 721);
+        assert_eq!(wide["fee_points"], 2161);
+        assert_eq!(narrow["n_periods"], 720);
+        assert_eq!(wide["n_periods"], 720);
+        assert!(wide.get("segments").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_rejects_too_wide_range() {
+        let (status, body) = request_estimate(request_body(0, 10_000 * HOUR)).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(
+            body["message"]
+                .as_str()
+                .unwrap()
+                .contains("exceeding the maximum")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_rejects_negative_timestamps() {
+        let (status, body) = request_estimate(request_body(-HOUR, HOUR)).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["message"],
+            "Timestamps must not be negative, got -3600 to 3600"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use message_handler::queue::message_queue::Queue;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::warn;
+
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    status: String,
+    queue_reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Reports whether the service is up and can reach the queue it dispatches jobs to.
+pub async fn health_check<Q: Queue + Send + Sync + 'static>(
+    State(queue): State<Arc<Q>>,
+) -> (StatusCode, Json<HealthResponse>) {
+    match queue.check_health().await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(HealthResponse {
+                status: "ok".to_string(),
+                queue_reachable: true,
+                error: None,
+            }),
+        ),
+        Err(e) => {
+            warn!("Health check failed: {}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(HealthResponse {
+                    status: "error".to_string(),
+                    queue_reachable: false,
+                    error: Some(e.to_string()),
+                }),
+            )
+        }
+    }
+}
@@ -1 +1,5 @@
+pub mod estimate;
+pub mod health;
 pub mod jobs;
+pub mod results;
+pub mod version;
@@ -0,0 +1,27 @@
+use axum::extract::Json;
+use message_handler::proof_composition::{
+    BonsaiProofProvider, ProofProvider, ProviderCapabilities, image_id_hex,
+};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    version: &'static str,
+    git_commit: &'static str,
+    /// Hex image id of the composition guest, absent when proofs aren't composed by a real guest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guest_image_id: Option<String>,
+    /// What the proof provider of this build can do.
+    provider_capabilities: ProviderCapabilities,
+}
+
+/// Reports which build is deployed, which guest it proves with and what its provider can do.
+pub async fn get_version() -> Json<VersionResponse> {
+    let provider = BonsaiProofProvider::new();
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: env!("GIT_COMMIT"),
+        guest_image_id: provider.image_id().map(image_id_hex),
+        provider_capabilities: provider.capabilities(),
+    })
+}
@@ -2,14 +2,13 @@
 use aws_config as _;
 use aws_sdk_sqs as _;
 use dotenv as _;
-use eyre as _;
-use tokio as _;
 use tracing_subscriber as _;
 
 mod handlers;
 mod routes;
 
-pub use routes::create_router;
+pub use handlers::jobs::DEFAULT_MAX_INFLIGHT;
+pub use routes::{create_router, results_router};
 
 #[cfg(test)]
 mod tests {
@@ -28,9 +27,6 @@ mod tests {
         let queue = Arc::new(SqsMessageQueue::new("test-queue-url".to_string(), config));
 
         // Ensure the router can be created without errors
-        let _router = create_router(queue).await;
-
-        // Basic verification - just check that we have a router
-        assert!(true, "Router was created successfully");
+        let _router = create_router(queue, true, None, DEFAULT_MAX_INFLIGHT).await;
     }
 }
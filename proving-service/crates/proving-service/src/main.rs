@@ -1,8 +1,10 @@
 use aws_config::{BehaviorVersion, defaults};
+use db::DbConnection;
 use eyre::Result;
+use message_handler::env_util::{bool_env, optional_env, u64_env};
 use message_handler::queue::sqs_message_queue::SqsMessageQueue;
-use proving_service::create_router;
-use std::{env, sync::Arc};
+use proving_service::{DEFAULT_MAX_INFLIGHT, create_router, results_router};
+use std::sync::Arc;
 use tokio::signal;
 use tracing::{Level, info};
 use tracing_subscriber::FmtSubscriber;
@@ -22,8 +24,10 @@ async fn main() -> Result<()> {
     dotenv::dotenv().ok();
 
     // Get the queue URL from environment variable
-    let queue_url = env::var("SQS_QUEUE_URL")
-        .unwrap_or_else(|_| "http://localhost:4566/000000000000/fossilQueue".to_string());
+    let queue_url = optional_env("SQS_QUEUE_URL")
+        .unwrap_or_else(|| "http://localhost:4566/000000000000/fossilQueue".to_string());
+    let proofs_enabled = bool_env("ENABLE_PROOF", true)?;
+    let max_inflight = u64_env("MAX_INFLIGHT", DEFAULT_MAX_INFLIGHT as u64)? as usize;
     info!("Using SQS Queue URL: {}", queue_url);
 
     // Load AWS SDK config from environment variables
@@ -32,8 +36,27 @@ async fn main() -> Result<()> {
 
     let queue = Arc::new(SqsMessageQueue::new(queue_url, config));
 
+    // Receipts are only served, and jobs in flight only limited, when the receipt database the
+    // proof handlers write to is set
+    let receipt_db = match optional_env("RECEIPT_DATABASE_URL") {
+        Some(receipt_database_url) => {
+            let receipt_db = DbConnection::new(&receipt_database_url).await?;
+            receipt_db.migrate().await?;
+            info!(
+                "Serving proof receipts from the receipt database, with up to {} jobs in flight",
+                max_inflight
+            );
+            Some(receipt_db)
+        }
+        None => None,
+    };
+
     // Create and start the HTTP server
-    let app = create_router(queue).await;
+    let mut app = create_router(queue, proofs_enabled, receipt_db.clone(), max_inflight).await;
+    if let Some(receipt_db) = receipt_db {
+        app = app.merge(results_router(receipt_db));
+    }
+
     let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 3001));
     info!("Starting HTTP server on {}", addr);
 
@@ -59,6 +82,5 @@ mod tests {
     #[test]
     fn test_main_builds() {
         // This test simply verifies that the main.rs file compiles
-        assert!(true);
     }
 }
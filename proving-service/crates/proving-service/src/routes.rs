@@ -1,27 +1,85 @@
-use axum::{Router, routing::post};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use db::DbConnection;
 use message_handler::{
-    queue::sqs_message_queue::SqsMessageQueue, services::job_dispatcher::JobDispatcher,
+    queue::{message_queue::Queue, sqs_message_queue::SqsMessageQueue},
+    services::job_dispatcher::JobDispatcher,
 };
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::handlers::estimate::estimate_proof;
+use crate::handlers::health::health_check;
+use crate::handlers::jobs::{
+    InFlightJobs, JobState, cancel_job, handle_batch_job_request, handle_job_request,
+};
+use crate::handlers::results::get_job_result;
+use crate::handlers::version::get_version;
 
-use crate::handlers::jobs::handle_job_request;
+/// Routes `GET /health`, which checks that `queue` can be reached.
+pub fn health_router<Q: Queue + Send + Sync + 'static>(queue: Arc<Q>) -> Router {
+    Router::new()
+        .route("/health", get(health_check::<Q>))
+        .with_state(queue)
+}
+
+/// Routes `GET /job_result/{job_id}`, which serves the proof receipts stored in `receipt_db`.
+pub fn results_router(receipt_db: Arc<DbConnection>) -> Router {
+    Router::new()
+        .route("/job_result/{job_id}", get(get_job_result))
+        .with_state(receipt_db)
+}
 
-pub async fn create_router(queue: Arc<SqsMessageQueue>) -> Router {
+/// Builds the API router. With a `job_db`, the receipt database the proof handlers record the
+/// jobs they are done with in, job requests arriving while `max_inflight` jobs are in flight
+/// are answered with a 503.
+pub async fn create_router(
+    queue: Arc<SqsMessageQueue>,
+    proofs_enabled: bool,
+    job_db: Option<Arc<DbConnection>>,
+    max_inflight: usize,
+) -> Router {
     info!("Setting up HTTP router");
 
-    let dispatcher = Arc::new(JobDispatcher::new(queue));
+    if !proofs_enabled {
+        warn!("ENABLE_PROOF is false, job requests will be answered as disabled");
+    }
+    if job_db.is_none() {
+        warn!("No receipt database to track jobs in, jobs in flight won't be limited");
+    }
+
+    let state = JobState {
+        dispatcher: Arc::new(JobDispatcher::new(queue.clone())),
+        proofs_enabled,
+        in_flight: job_db.map(|job_db| InFlightJobs::new(job_db, max_inflight.max(1))),
+    };
 
     Router::new()
-        .route("/api/job", post(handle_job_request))
-        .with_state(dispatcher)
+        .route("/api/job", post(handle_job_request::<SqsMessageQueue>))
+        .route(
+            "/api/jobs",
+            post(handle_batch_job_request::<SqsMessageQueue>),
+        )
+        .route("/job/{job_id}/cancel", post(cancel_job::<SqsMessageQueue>))
+        .with_state(state)
+        .route("/version", get(get_version))
+        .route("/estimate", post(estimate_proof))
+        .merge(health_router(queue))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::handlers::jobs::DEFAULT_MAX_INFLIGHT;
     use async_trait::async_trait;
-    use message_handler::queue::message_queue::{Queue, QueueError, QueueMessage};
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use message_handler::queue::message_queue::{QueueError, QueueMessage};
+    use tower::ServiceExt;
 
     // Mock queue implementation for testing
     #[derive(Debug, Clone)]
@@ -52,6 +110,16 @@ mod tests {
         async fn delete_message(&self, _message: &QueueMessage) -> Result<(), QueueError> {
             unimplemented!("Not needed for these tests")
         }
+
+        async fn check_health(&self) -> Result<(), QueueError> {
+            if self.should_fail {
+                Err(QueueError::HealthCheckError(
+                    "Mock queue unreachable".to_string(),
+                ))
+            } else {
+                Ok(())
+            }
+        }
     }
 
     // Create a wrapper to make MockQueue compatible with SqsMessageQueue interface
@@ -104,10 +172,72 @@ mod tests {
         let sqs_queue: SqsMessageQueue = test_queue.into();
 
         // Create the router
-        let _app = create_router(Arc::new(sqs_queue)).await;
+        // Creating the router without panicking is what's under test here
+        let _app = create_router(Arc::new(sqs_queue), true, None, DEFAULT_MAX_INFLIGHT).await;
+    }
 
-        // Simple assertion that we created a router
-        // In a real test, we might want to test the router by making requests
-        assert!(true, "Router created successfully");
+    async fn get_health(queue: MockQueue) -> (StatusCode, serde_json::Value) {
+        let response = health_router(Arc::new(queue))
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_version_reports_build_info() {
+        let sqs_queue: SqsMessageQueue = TestSqsMessageQueue::new(MockQueue::new(false)).into();
+        let response = create_router(Arc::new(sqs_queue), true, None, DEFAULT_MAX_INFLIGHT)
+            .await
+            .oneshot(Request::get("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(body["git_commit"], env!("GIT_COMMIT"));
+
+        #[cfg(feature = "proof-composition")]
+        assert_eq!(body["guest_image_id"].as_str().map(str::len), Some(64));
+        #[cfg(not(feature = "proof-composition"))]
+        assert!(body.get("guest_image_id").is_none());
+
+        let proves = cfg!(feature = "proof-composition");
+        assert_eq!(
+            body["provider_capabilities"],
+            serde_json::json!({
+                "can_prove": proves,
+                "supports_separate": false,
+                "supports_onchain_calldata": proves,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_reachable_queue() {
+        let (status, body) = get_health(MockQueue::new(false)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            serde_json::json!({"status": "ok", "queue_reachable": true})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_unreachable_queue() {
+        let (status, body) = get_health(MockQueue::new(true)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["queue_reachable"], false);
+        assert_eq!(
+            body["error"],
+            "Failed to reach queue: Mock queue unreachable"
+        );
     }
 }
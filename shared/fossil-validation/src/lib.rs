@@ -0,0 +1,221 @@
+/// Longest window a single proof may cover: 8 months of hourly data, in seconds.
+pub const MAX_WINDOW_SECS: i64 = 5760 * 3600;
+
+/// The settlement windows a pricing request asks proofs for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Twap,
+    ReservePrice,
+    MaxReturn,
+}
+
+impl Window {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Twap => "TWAP",
+            Self::ReservePrice => "Reserve Price",
+            Self::MaxReturn => "Max Return",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The window doesn't start before it ends.
+    InvalidRange(Window),
+    /// The window is wider than [`MAX_WINDOW_SECS`].
+    SpanTooLong(Window),
+    /// The window isn't contained in the reserve price window.
+    NotNested(Window),
+}
+
+impl ValidationError {
+    /// The window that failed validation.
+    pub const fn window(&self) -> Window {
+        match self {
+            Self::InvalidRange(window) | Self::SpanTooLong(window) | Self::NotNested(window) => {
+                *window
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidRange(window) => {
+                write!(f, "Invalid time range for {} calculation.", window.name())
+            }
+            Self::SpanTooLong(window) => write!(
+                f,
+                "Time range for {} calculation exceeds the maximum of {} seconds.",
+                window.name(),
+                MAX_WINDOW_SECS
+            ),
+            Self::NotNested(window) => write!(
+                f,
+                "Time range for {} calculation must lie within the Reserve Price range.",
+                window.name()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks the `(start, end)` windows of a pricing request: each must start before it ends and
+/// span at most [`MAX_WINDOW_SECS`], and the TWAP and max return windows must lie within the
+/// reserve price window, whose fee history covers both.
+pub fn validate_windows(
+    twap: (i64, i64),
+    reserve_price: (i64, i64),
+    max_return: (i64, i64),
+) -> Result<(), ValidationError> {
+    match window_errors(twap, reserve_price, max_return)
+        .into_iter()
+        .next()
+    {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Checks the windows like [`validate_windows`], returning the error of every invalid window
+/// instead of the first. Nesting is only checked for valid windows within a valid reserve
+/// price window.
+pub fn window_errors(
+    twap: (i64, i64),
+    reserve_price: (i64, i64),
+    max_return: (i64, i64),
+) -> Vec<ValidationError> {
+    let range_error = |window, (start, end): (i64, i64)| {
+        if start >= end {
+            Some(ValidationError::InvalidRange(window))
+        } else if end.abs_diff(start) > MAX_WINDOW_SECS.unsigned_abs() {
+            Some(ValidationError::SpanTooLong(window))
+        } else {
+            None
+        }
+    };
+
+    let mut errors = Vec::new();
+    let twap_error = range_error(Window::Twap, twap);
+    let reserve_price_error = range_error(Window::ReservePrice, reserve_price);
+    let max_return_error = range_error(Window::MaxReturn, max_return);
+    let reserve_price_valid = reserve_price_error.is_none();
+    errors.extend(twap_error.clone());
+    errors.extend(reserve_price_error);
+    errors.extend(max_return_error.clone());
+
+    let (reserve_start, reserve_end) = reserve_price;
+    for (window, (start, end), error) in [
+        (Window::Twap, twap, twap_error),
+        (Window::MaxReturn, max_return, max_return_error),
+    ] {
+        if reserve_price_valid && error.is_none() && (start < reserve_start || end > reserve_end) {
+            errors.push(ValidationError::NotNested(window));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+
+    #[test]
+    fn test_accepts_nested_windows() {
+        let reserve_price = (0, 90 * DAY);
+        let twap = (60 * DAY, 90 * DAY);
+        let max_return = (30 * DAY, 90 * DAY);
+
+        assert_eq!(validate_windows(twap, reserve_price, max_return), Ok(()));
+        // Identical windows are nested too
+        assert_eq!(
+            validate_windows(reserve_price, reserve_price, reserve_price),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_rejects_reversed_or_empty_window() {
+        let valid = (0, 100);
+
+        assert_eq!(
+            validate_windows((100, 0), valid, valid),
+            Err(ValidationError::InvalidRange(Window::Twap))
+        );
+        assert_eq!(
+            validate_windows(valid, (50, 50), valid),
+            Err(ValidationError::InvalidRange(Window::ReservePrice))
+        );
+        assert_eq!(
+            validate_windows(valid, valid, (100, 0)),
+            Err(ValidationError::InvalidRange(Window::MaxReturn))
+        );
+    }
+
+    #[test]
+    fn test_rejects_window_over_max_span() {
+        let too_long = (0, MAX_WINDOW_SECS + 1);
+
+        assert_eq!(
+            validate_windows((0, 100), too_long, (0, 100)),
+            Err(ValidationError::SpanTooLong(Window::ReservePrice))
+        );
+        assert_eq!(
+            validate_windows((0, 100), (0, MAX_WINDOW_SECS), (0, 100)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_rejects_window_outside_reserve_price() {
+        let reserve_price = (100, 200);
+
+        assert_eq!(
+            validate_windows((50, 150), reserve_price, (100, 200)),
+            Err(ValidationError::NotNested(Window::Twap))
+        );
+        assert_eq!(
+            validate_windows((100, 200), reserve_price, (150, 250)),
+            Err(ValidationError::NotNested(Window::MaxReturn))
+        );
+    }
+
+    #[test]
+    fn test_window_errors_reports_every_invalid_window() {
+        let too_long = (0, MAX_WINDOW_SECS + 1);
+
+        assert_eq!(
+            window_errors((100, 0), (0, 200), too_long),
+            [
+                ValidationError::InvalidRange(Window::Twap),
+                ValidationError::SpanTooLong(Window::MaxReturn),
+            ]
+        );
+        assert_eq!(
+            window_errors((50, 150), (100, 200), (150, 250)),
+            [
+                ValidationError::NotNested(Window::Twap),
+                ValidationError::NotNested(Window::MaxReturn),
+            ]
+        );
+        assert!(window_errors((0, 100), (0, 100), (0, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!(
+            ValidationError::InvalidRange(Window::Twap).to_string(),
+            "Invalid time range for TWAP calculation."
+        );
+        assert_eq!(
+            ValidationError::NotNested(Window::MaxReturn).to_string(),
+            "Time range for Max Return calculation must lie within the Reserve Price range."
+        );
+    }
+}